@@ -0,0 +1,185 @@
+use std::io::{self, BufRead, Write};
+use serde_json::{json, Value};
+use propinva::engine::{parse_answer, Settings};
+use propinva::history::HistoryError;
+use propinva::problem::{relation, Problem};
+use propinva::rational::Rational;
+use crate::store::Store;
+
+/// The answers typed on `line` for `problem`, separated by spaces or as a JSON array: numbers,
+/// `yes`/`no` for equivalences and `<`, `=` or `>` for comparisons.
+fn parse_answers(problem: &Problem, line: &str, limit: u32) -> Result<Vec<Rational>, String> {
+    let tokens: Vec<String> = if line.starts_with('[') {
+        let values: Vec<Value> = serde_json::from_str(line).map_err(|error| format!("malformed answers: {}", error))?;
+        values.into_iter()
+            .map(|value| match value {
+                Value::String(text) => {text}
+                other => {other.to_string()}
+            })
+            .collect()
+    } else {
+        line.split_whitespace().map(String::from).collect()
+    };
+    if tokens.len() != problem.blanks() {
+        return Err(format!("expected {} answers, got {}", problem.blanks(), tokens.len()));
+    }
+    tokens.iter().enumerate()
+        .map(|(blank, token)| {
+            let answer = match (problem, token.to_ascii_lowercase().as_str()) {
+                (Problem::Equivalent(_), "yes" | "y") => {Rational::whole(1)}
+                (Problem::Equivalent(_), "no" | "n") => {return Ok(Rational::ZERO)}
+                (Problem::Compare(_), "<") => {Rational::from(relation(std::cmp::Ordering::Less))}
+                (Problem::Compare(_), "=") => {Rational::from(relation(std::cmp::Ordering::Equal))}
+                (Problem::Compare(_), ">") => {Rational::from(relation(std::cmp::Ordering::Greater))}
+                (Problem::Equivalent(_) | Problem::Compare(_), _) => {Rational::ZERO}
+                (_, number) => {parse_answer(problem, blank, number, limit)}
+            };
+            if answer.is_zero() {Err(format!("'{}' is not a valid answer", token))} else {Ok(answer)}
+        })
+        .collect()
+}
+
+fn emit(output: &mut impl Write, line: Value) -> io::Result<()> {
+    writeln!(output, "{}", line)?;
+    output.flush()
+}
+
+fn emit_saved(output: &mut impl Write, saved: Result<(), HistoryError>) -> io::Result<()> {
+    match saved {
+        Ok(()) => {Ok(())}
+        Err(error) => {emit(output, json!({"type": "error", "message": format!("not saved: {}", error)}))}
+    }
+}
+
+/// One session without prompts for scripts and other programs: every problem is written as a line of JSON,
+/// answered by a line of input and followed by a verdict line, graded by the engine like in the window.
+/// The session ends when it is over or at the end of the input, with a summary line.
+pub fn run(mut store: Store, settings: Settings, mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    if let Some(error) = &store.error {
+        emit(&mut output, json!({"type": "error", "message": format!("not saved: {}", error)}))?;
+    }
+    let mut session = store.session(settings);
+    session.start();
+    'session: loop {
+        let problem = session.problem();
+        emit(&mut output, json!({
+            "type": "problem",
+            "index": session.exercise_count() + 1,
+            "kind": problem.kind().to_string(),
+            "text": problem.to_string(),
+            "blanks": problem.blanks(),
+            "problem": problem,
+        }))?;
+        let answers = loop {
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                break 'session;
+            }
+            match parse_answers(&problem, line.trim(), session.input_limit()) {
+                Ok(answers) => {break answers}
+                Err(message) => {emit(&mut output, json!({"type": "error", "message": message}))?}
+            }
+        };
+        let correct = session.answer(answers.clone());
+        emit(&mut output, json!({
+            "type": "verdict",
+            "correct": correct,
+            "answers": problem.format_answers(&answers),
+            "solution": problem.format_answers(&problem.solution()),
+            "mistake": problem.grade(&answers).err().map(|mistake| mistake.to_string()),
+        }))?;
+        if session.is_over() {
+            break;
+        }
+        session.next();
+    }
+
+    let summary = session.finish();
+    emit(&mut output, json!({
+        "type": "summary",
+        "headline": summary.headline(),
+        "correct": summary.correct,
+        "errors": summary.errors,
+        "accuracy": summary.accuracy,
+        "elapsed": summary.elapsed.as_secs_f64(),
+        "ending": summary.ending,
+        "breakdown": session.weakness().breakdown(),
+        "advice": session.weakness().advice(),
+        "seed": session.seed(),
+    }))?;
+    let saved = store.record(&session);
+    emit_saved(&mut output, saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use propinva::engine::{Session, SessionLength};
+    use propinva::problem::{relation_symbol, Kind};
+    use super::*;
+
+    /// The solution of `problem` as a script types it.
+    fn typed(problem: &Problem) -> String {
+        let answers: Vec<String> = problem.solution().iter()
+            .map(|answer| match problem {
+                Problem::Equivalent(_) => {String::from(if answer.is_zero() {"no"} else {"yes"})}
+                Problem::Compare(_) => {String::from(relation_symbol(answer.to_u32().unwrap_or(0)))}
+                _ => {answer.to_string()}
+            })
+            .collect();
+        answers.join(" ")
+    }
+
+    /// The lines of JSON written for `input`.
+    fn run_lines(settings: Settings, input: String) -> Vec<Value> {
+        let mut output = Vec::new();
+        run(Store::default(), settings, Cursor::new(input), &mut output).unwrap();
+        String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn verdicts_and_summary_match_the_engine() {
+        let settings = Settings{seed: Some(7), length: SessionLength::Count(3), ..Settings::default()};
+        let mut session = Session::new(settings);
+        session.start();
+        let mut input = String::from("not a number\n");
+        let mut verdicts = Vec::new();
+        for index in 0..3 {
+            let problem = session.problem();
+            let answer = problem.solution()[0].to_u32().unwrap_or(0) + (index == 1) as u32;
+            // the answers may come as a JSON array too
+            input.push_str(&if index == 2 {format!("[{}]\n", answer)} else {format!("{}\n", answer)});
+            verdicts.push(session.answer(vec![Rational::from(answer)]));
+            session.next();
+        }
+        let summary = session.finish();
+
+        let lines = run_lines(settings, input);
+        let kinds: Vec<&str> = lines.iter().map(|line| line["type"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["problem", "error", "verdict", "problem", "verdict", "problem", "verdict", "summary"]);
+        let correct: Vec<bool> = lines.iter().filter(|line| line["type"] == "verdict").map(|line| line["correct"].as_bool().unwrap()).collect();
+        assert_eq!(correct, verdicts);
+        let last = &lines[7];
+        assert_eq!(last["headline"], summary.headline());
+        assert_eq!((last["correct"].as_u64(), last["errors"].as_u64()), (Some(summary.correct as u64), Some(summary.errors as u64)));
+        assert_eq!(last["accuracy"].as_f64(), Some(summary.accuracy as f64));
+        assert_eq!(last["seed"], 7);
+    }
+
+    #[test]
+    fn every_kind_can_be_answered() {
+        let settings = Settings{seed: Some(3), length: SessionLength::Count(40), kind: Kind::Mixed, ..Settings::default()};
+        let mut session = Session::new(settings);
+        session.start();
+        let mut input = String::new();
+        for _ in 0..40 {
+            input.push_str(&format!("{}\n", typed(&session.problem())));
+            session.answer(session.problem().solution());
+            session.next();
+        }
+        let lines = run_lines(settings, input);
+        assert!(lines.iter().all(|line| line["type"] != "error"), "{:?}", lines);
+        assert_eq!(lines.iter().filter(|line| line["type"] == "verdict" && line["correct"] == true).count(), 40);
+        assert_eq!(lines.last().map(|line| line["errors"].clone()), Some(Value::from(0)));
+    }
+}
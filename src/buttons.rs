@@ -73,3 +73,150 @@ pub fn quit_button() -> Button<'static, Message, Theme, Renderer> {
     .width(Length::Fixed(100.0))
     .height(Length::Fixed(60.0))
 }
+
+pub fn add_profile_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Add")
+            .size(18)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(GreenButtonStyleSheet::new())
+    .width(Length::Fixed(70.0))
+    .height(Length::Fixed(34.0))
+}
+
+pub fn rename_profile_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Rename")
+            .size(18)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(BlueButtonStyleSheet::new())
+    .width(Length::Fixed(70.0))
+    .height(Length::Fixed(34.0))
+}
+
+pub fn delete_profile_button(confirm: bool) -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text(if confirm {"Sure?"} else {"Delete"})
+            .size(18)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(RedButtonStyleSheet::new())
+    .width(Length::Fixed(70.0))
+    .height(Length::Fixed(34.0))
+}
+
+pub fn review_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Review")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(BlueButtonStyleSheet::new())
+    .width(Length::Fixed(120.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn export_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Export")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(BlueButtonStyleSheet::new())
+    .width(Length::Fixed(120.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn back_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Back")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).on_press(Message::CloseReview)
+    .style(GreenButtonStyleSheet::new())
+    .width(Length::Fixed(100.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn previous_attempt_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("<")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(BlueButtonStyleSheet::new())
+    .width(Length::Fixed(60.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn next_attempt_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text(">")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(BlueButtonStyleSheet::new())
+    .width(Length::Fixed(60.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn retry_mistakes_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Practice my mistakes")
+            .size(18)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(GreenButtonStyleSheet::new())
+    .width(Length::Fixed(180.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn yes_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Yes")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).on_press(Message::Choose(true))
+    .style(GreenButtonStyleSheet::new())
+    .width(Length::Fixed(100.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn no_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("No")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).on_press(Message::Choose(false))
+    .style(RedButtonStyleSheet::new())
+    .width(Length::Fixed(100.0))
+    .height(Length::Fixed(60.0))
+}
+
+pub fn relation_button(gap: usize, symbol: &str) -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text(symbol)
+            .size(30)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).on_press(Message::CycleRelation(gap))
+    .style(BlueButtonStyleSheet::new())
+    .width(Length::Fixed(50.0))
+    .height(Length::Fixed(50.0))
+}
+
+pub fn hint_button() -> Button<'static, Message, Theme, Renderer> {
+    button(
+        text("Hint")
+            .size(28)
+            .horizontal_alignment(alignment::Horizontal::Center)
+            .vertical_alignment(alignment::Vertical::Center)
+    ).style(GreenButtonStyleSheet::new())
+    .width(Length::Fixed(100.0))
+    .height(Length::Fixed(60.0))
+}
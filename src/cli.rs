@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use propinva::difficulty::{Bounds, Difficulty, Ranges};
+use propinva::engine::{RetryOptions, SessionLength, Settings};
+use propinva::history::History;
+use propinva::problem::Kind;
+
+#[derive(Parser, Debug, Default)]
+#[command(name = "propinva", version, about = "Simple tool to exercise on the invariant property of fractions")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Seed for the exercise generator, the same seed always yields the same exercises
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+    /// Number of exercises in a session
+    #[arg(long, group = "length")]
+    pub count: Option<u32>,
+    /// Practise until Finish is pressed
+    #[arg(long, group = "length")]
+    pub unlimited: bool,
+    /// Length of a time-boxed session in minutes
+    #[arg(long, group = "length")]
+    pub minutes: Option<u64>,
+    /// Difficulty preset: easy, medium, hard, expert or custom
+    #[arg(long, global = true)]
+    pub difficulty: Option<Difficulty>,
+    /// Custom range of multipliers up to 999, e.g. 2-10 (implies --difficulty custom)
+    #[arg(long, global = true)]
+    pub multipliers: Option<Bounds>,
+    /// Custom range of base numerators and denominators up to 999, e.g. 1-12 (implies --difficulty custom)
+    #[arg(long, global = true)]
+    pub numbers: Option<Bounds>,
+    /// Kind of exercises: missing-term, scale-down, simplify, equivalent, chain, compare, find-multiplier, mixed-numbers, decimals or mixed
+    #[arg(long, global = true)]
+    pub kind: Option<Kind>,
+    /// Put minus signs on the terms of missing-term exercises
+    #[arg(long, global = true)]
+    pub signed: bool,
+    /// Bias the exercises toward the weak multipliers and numbers, from 0 (off) to 1 (strongest)
+    #[arg(long, value_parser = parse_strength)]
+    pub adaptive: Option<f32>,
+    /// Serve the facts due for review before new exercises
+    #[arg(long)]
+    pub review_due: bool,
+    /// Serve the mistakes in random order when practising them
+    #[arg(long)]
+    pub shuffle_mistakes: bool,
+    /// Hide a different term of the mistakes when practising them
+    #[arg(long)]
+    pub change_hidden_term: bool,
+    /// History file where finished sessions are stored (defaults to the platform data directory)
+    #[arg(long, global = true)]
+    pub history: Option<PathBuf>,
+    /// Student profile to select, created on the first finished session if missing
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Run in the terminal instead of a window, reading one answer per line
+    #[arg(long, conflicts_with = "batch")]
+    pub tui: bool,
+    /// Run one session without prompts: problems, verdicts and the summary as lines of JSON, answers read from stdin
+    #[arg(long)]
+    pub batch: bool,
+}
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Export printable problems of the session settings with their answer key, without opening a window
+    Worksheet {
+        /// Number of problems
+        #[arg(long, default_value_t = 20)]
+        problems: usize,
+        /// File to write, .pdf for one document or .svg for one file per page
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Export every problem of the stored sessions of the profile with the times table errors, without opening a window
+    Report {
+        /// Only the last sessions, this many
+        #[arg(long)]
+        last: Option<usize>,
+        /// File to write, .json for one file or .csv for the problems with -sessions.csv and -tables.csv next to it
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Write a web page of the progress of the profile with charts, to send to parents
+    Progress {
+        /// Only the last sessions, this many
+        #[arg(long)]
+        last: Option<usize>,
+        /// One page per profile, in the folder given as output
+        #[arg(long)]
+        all: bool,
+        /// HTML file to write, or folder with --all
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+}
+
+impl Cli {
+    pub fn history_path(&self) -> Option<PathBuf> {
+        self.history.clone().or_else(History::default_path)
+    }
+    /// Whether any option changing the sessions was given, overriding the settings saved in the profile.
+    pub fn has_session_options(&self) -> bool {
+        self.count.is_some() || self.unlimited || self.minutes.is_some() || self.difficulty.is_some() || self.multipliers.is_some() || self.numbers.is_some()
+            || self.kind.is_some() || self.signed || self.review_due || self.adaptive.is_some() || self.shuffle_mistakes || self.change_hidden_term
+    }
+    pub fn settings(&self) -> Settings {
+        let length = if let Some(count) = self.count {
+            SessionLength::Count(count.max(1))
+        } else if self.unlimited {
+            SessionLength::Unlimited
+        } else if let Some(minutes) = self.minutes {
+            SessionLength::Timed(Duration::from_secs(minutes.max(1)*60))
+        } else {
+            SessionLength::default()
+        };
+        let custom = self.multipliers.is_some() || self.numbers.is_some();
+        let defaults = Ranges::default();
+        Settings{
+            seed: self.seed,
+            length,
+            difficulty: if custom {Difficulty::Custom} else {self.difficulty.unwrap_or_default()},
+            custom_ranges: Ranges{
+                multiplier: self.multipliers.unwrap_or(defaults.multiplier),
+                number: self.numbers.unwrap_or(defaults.number),
+            }.normalized(),
+            adaptive: self.adaptive.unwrap_or(0.0),
+            review_due: self.review_due,
+            kind: self.kind.unwrap_or_default(),
+            signed: self.signed,
+            retry: RetryOptions{
+                shuffle: self.shuffle_mistakes,
+                change_hidden_term: self.change_hidden_term,
+            },
+        }
+    }
+}
+
+fn parse_strength(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(strength) if (0.0..=1.0).contains(&strength) => {Ok(strength)}
+        _ => {Err(format!("expected a strength between 0 and 1, got '{}'", s))}
+    }
+}
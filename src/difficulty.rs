@@ -0,0 +1,115 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// The biggest multiplier or base number, so the terms and the answers stay far from overflowing.
+pub const MAX_BOUND: u32 = 999;
+
+/// An inclusive `min-max` range of numbers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub min: u32,
+    pub max: u32,
+}
+impl Bounds {
+    pub const fn new(min: u32, max: u32) -> Self {
+        Bounds{min, max}
+    }
+    /// Makes sure `min` is at least `floor`, `max` is not below `min` and neither is above `MAX_BOUND`.
+    fn normalized(self, floor: u32) -> Self {
+        let min = self.min.clamp(floor, MAX_BOUND);
+        Bounds{min, max: self.max.clamp(min, MAX_BOUND)}
+    }
+}
+impl Display for Bounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.min, self.max)
+    }
+}
+impl FromStr for Bounds {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s.split_once('-').ok_or(format!("expected a range like 2-10, got '{}'", s))?;
+        let min = min.trim().parse().map_err(|_| format!("invalid minimum '{}'", min))?;
+        let max = max.trim().parse().map_err(|_| format!("invalid maximum '{}'", max))?;
+        if max > MAX_BOUND {
+            return Err(format!("the maximum {} is above {}", max, MAX_BOUND));
+        }
+        Ok(Bounds{min, max})
+    }
+}
+
+/// The multipliers and the base numerators/denominators the generator draws from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ranges {
+    pub multiplier: Bounds,
+    pub number: Bounds,
+}
+impl Ranges {
+    pub fn normalized(self) -> Self {
+        Ranges{
+            multiplier: self.multiplier.normalized(2),
+            number: self.number.normalized(1),
+        }
+    }
+    /// The biggest term an exercise can contain.
+    pub fn max_term(&self) -> u32 {
+        self.multiplier.max * self.number.max
+    }
+    /// Answers must stay below this limit, 1000 for the standard ranges.
+    pub fn input_limit(&self) -> u32 {
+        let mut limit = 1000;
+        while limit <= self.max_term() {
+            limit *= 10;
+        }
+        limit
+    }
+}
+impl Default for Ranges {
+    fn default() -> Self {
+        Difficulty::Medium.ranges(None)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Expert,
+    Custom,
+}
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert, Difficulty::Custom];
+
+    /// The ranges of the preset, or `custom` (the standard ranges if missing) for `Difficulty::Custom`.
+    pub fn ranges(&self, custom: Option<Ranges>) -> Ranges {
+        match self {
+            Difficulty::Easy    => {Ranges{multiplier: Bounds::new(2, 5), number: Bounds::new(1, 5)}},
+            Difficulty::Medium  => {Ranges{multiplier: Bounds::new(2, 10), number: Bounds::new(1, 12)}},
+            Difficulty::Hard    => {Ranges{multiplier: Bounds::new(2, 12), number: Bounds::new(1, 20)}},
+            Difficulty::Expert  => {Ranges{multiplier: Bounds::new(2, 20), number: Bounds::new(10, 99)}},
+            Difficulty::Custom  => {custom.unwrap_or_else(|| Difficulty::Medium.ranges(None)).normalized()},
+        }
+    }
+}
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy    => {write!(f, "Easy")},
+            Difficulty::Medium  => {write!(f, "Medium")},
+            Difficulty::Hard    => {write!(f, "Hard")},
+            Difficulty::Expert  => {write!(f, "Expert")},
+            Difficulty::Custom  => {write!(f, "Custom")},
+        }
+    }
+}
+impl FromStr for Difficulty {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL.into_iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(s))
+            .ok_or(format!("unknown difficulty '{}', expected one of easy, medium, hard, expert, custom", s))
+    }
+}
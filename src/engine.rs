@@ -0,0 +1,728 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rand::{Rng, SeedableRng};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use crate::difficulty::{Bounds, Difficulty, Ranges};
+use crate::problem::{Fraction, Kind, Notation, Problem};
+use crate::rational::Rational;
+use crate::schedule::Fact;
+use crate::weakness::{Tally, Weakness};
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum Mode {
+    #[default]
+    Start,
+    Exercising,
+    Result,
+    FinalEvaluation
+}
+impl Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Start             => {write!(f, "Start")},
+            Mode::Exercising        => {write!(f, "Exercising")},
+            Mode::Result            => {write!(f, "Result")},
+            Mode::FinalEvaluation   => {write!(f, "FinalEvaluation")},
+        }
+    }
+}
+
+/// A single `num_a/den_a = num_b/den_b` problem; `input_field_number` is the hidden term
+/// (0: num_a, 1: den_a, 2: num_b, 3: den_b).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Exercise {
+    pub num_a: u32,
+    pub den_a: u32,
+    pub num_b: u32,
+    pub den_b: u32,
+    pub input_field_number: u32,
+    /// Which terms carry a minus sign, in the order of `input_field_number`; the terms above are their sizes.
+    #[serde(default)]
+    pub negative: [bool; 4],
+}
+impl Exercise {
+    /// A random exercise of `ranges`, drawing the multipliers and base numbers of `weakness` more often,
+    /// the more so the higher `strength` (0 is uniform, 1 the strongest bias).
+    pub fn generate_adaptive<R: Rng>(rng: &mut R, ranges: Ranges, weakness: &Weakness, strength: f32) -> Self {
+        let mul = draw(rng, ranges.multiplier, &weakness.multipliers, strength);
+        let num = draw(rng, ranges.number, &weakness.numbers, strength);
+        let den = draw(rng, ranges.number, &weakness.numbers, strength);
+        Self::scaled(rng, num, den, mul)
+    }
+    /// A big fraction to scale down to a hidden term of a `mul` times smaller one, which is not always
+    /// in lowest terms (`36/48 = ?/4` as well as `36/48 = 9/?`).
+    pub fn scaled_down<R: Rng>(rng: &mut R, ranges: Ranges, weakness: &Weakness, strength: f32) -> Self {
+        let mul = draw(rng, ranges.multiplier, &weakness.multipliers, strength);
+        let num = draw(rng, ranges.number, &weakness.numbers, strength);
+        let den = draw(rng, ranges.number, &weakness.numbers, strength);
+        let small = if rng.gen_bool(0.5) {Fraction::new(num, den).reduced()} else {Fraction::new(num, den)};
+
+        Exercise{
+            num_a: small.num*mul,
+            den_a: small.den*mul,
+            num_b: small.num,
+            den_b: small.den,
+            input_field_number: rng.gen_range(2..4),
+            negative: [false; 4],
+        }
+    }
+    /// `num/den` next to its `mul` times bigger equivalent, in random order and with a random hidden term.
+    pub fn scaled<R: Rng>(rng: &mut R, num: u32, den: u32, mul: u32) -> Self {
+        let a_or_b = rng.gen_bool(0.5);
+
+        Exercise{
+            num_a: if a_or_b {num} else {num*mul},
+            den_a: if a_or_b {den} else {den*mul},
+            num_b: if a_or_b {num*mul} else {num},
+            den_b: if a_or_b {den*mul} else {den},
+            input_field_number: rng.gen_range(0..4),
+            negative: [false; 4],
+        }
+    }
+    /// The same fractions with random minus signs, both of the same sign (`-2/3 = 4/-6`).
+    pub fn with_signs<R: Rng>(&self, rng: &mut R) -> Self {
+        let (num_a, den_a, num_b) = (rng.gen_bool(0.5), rng.gen_bool(0.5), rng.gen_bool(0.5));
+        Exercise{
+            negative: [num_a, den_a, num_b, num_a ^ den_a ^ num_b],
+            ..*self
+        }
+    }
+    /// The term `field` with its sign.
+    pub fn signed_term(&self, field: u32) -> i64 {
+        let size = [self.num_a, self.den_a, self.num_b, self.den_b][field as usize] as i64;
+        if self.negative[field as usize] {-size} else {size}
+    }
+    pub fn solution(&self) -> u32 {
+        match self.input_field_number {
+            0 => {self.num_a}
+            1 => {self.den_a}
+            2 => {self.num_b}
+            3 => {self.den_b}
+            _ => {panic!("Error: input field different from expected [0, 1, 2, 3]")}
+        }
+    }
+    /// The same fractions with a different term hidden.
+    pub fn with_other_hidden<R: Rng>(&self, rng: &mut R) -> Self {
+        Exercise{
+            input_field_number: (self.input_field_number + rng.gen_range(1..4)) % 4,
+            ..*self
+        }
+    }
+    /// Whether the hidden term belongs to the smaller fraction, so that finding it takes a division.
+    pub fn is_dividing(&self) -> bool {
+        let hidden_in_a = self.input_field_number < 2;
+        let a_is_smaller = self.num_a < self.num_b || self.den_a < self.den_b;
+        hidden_in_a == a_is_smaller
+    }
+    /// The (multiplier, base number) pair of the terms sharing a row with the hidden one.
+    pub fn factors(&self) -> (u32, u32) {
+        let (a, b) = match self.input_field_number {
+            0 | 2 => {(self.num_a, self.num_b)}
+            1 | 3 => {(self.den_a, self.den_b)}
+            _ => {panic!("Error: input field different from expected [0, 1, 2, 3]")}
+        };
+        if a > b {(a/b, b)} else {(b/a, a)}
+    }
+}
+impl Display for Exercise {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let term = |field: u32| if self.input_field_number == field {String::from("?")} else {self.signed_term(field).to_string()};
+        write!(f, "{}/{} = {}/{}", term(0), term(1), term(2), term(3))
+    }
+}
+
+/// A value of `bounds`, drawn more often the weaker it is in `tally` when `strength` is above 0.
+pub(crate) fn draw<R: Rng>(rng: &mut R, bounds: Bounds, tally: &Tally, strength: f32) -> u32 {
+    if strength <= 0.0 {
+        return rng.gen_range(bounds.min..=bounds.max);
+    }
+    match WeightedIndex::new(tally.weights(bounds, strength)) {
+        Ok(index) => {bounds.min + index.sample(rng) as u32}
+        Err(_) => {rng.gen_range(bounds.min..=bounds.max)}
+    }
+}
+
+/// When a session is over: after a fixed number of exercises, never (until Finish) or after some time.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SessionLength {
+    Count(u32),
+    Unlimited,
+    Timed(Duration),
+}
+impl Display for SessionLength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionLength::Count(count)     => {write!(f, "{} exercises", count)},
+            SessionLength::Unlimited        => {write!(f, "Unlimited")},
+            SessionLength::Timed(duration)  => {write!(f, "{} minutes", duration.as_secs()/60)},
+        }
+    }
+}
+impl Default for SessionLength {
+    fn default() -> Self {
+        SessionLength::Count(20)
+    }
+}
+
+/// Which limit ended the session.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Ending {
+    CountReached,
+    TimeUp,
+    Stopped,
+    MistakesFixed,
+}
+
+/// The numbers shown on the final evaluation screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Summary {
+    pub correct: u32,
+    pub errors: u32,
+    pub accuracy: f32,
+    pub elapsed: Duration,
+    pub ending: Ending,
+}
+impl Summary {
+    pub fn headline(&self) -> String {
+        let count = self.correct + self.errors;
+        match self.ending {
+            Ending::CountReached    => {format!("You completed all {} exercises!", count)},
+            Ending::TimeUp          => {format!("Time's up! You did {} exercises in {}", count, format_duration(self.elapsed))},
+            Ending::MistakesFixed   => {String::from("You fixed all your mistakes!")},
+            Ending::Stopped         => {format!("You practised {} exercises in {}", count, format_duration(self.elapsed))},
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+/// Formats a duration as `m:ss`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds/60, seconds%60)
+}
+
+/// One answered problem: what was typed, whether it was right and how long it took.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attempt {
+    pub problem: Problem,
+    pub answers: Vec<Rational>,
+    pub correct: bool,
+    pub time: Duration,
+    /// Whether the hint was shown before answering.
+    #[serde(default)]
+    pub hinted: bool,
+}
+
+/// How the mistakes of a session are served again by `Session::retry_mistakes`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetryOptions {
+    pub shuffle: bool,
+    pub change_hidden_term: bool,
+}
+
+/// How a session is generated; a fixed `seed` replays the same exercises every time.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    pub seed: Option<u64>,
+    pub length: SessionLength,
+    pub difficulty: Difficulty,
+    pub custom_ranges: Ranges,
+    #[serde(default)]
+    pub retry: RetryOptions,
+    /// How strongly exercises are biased toward weak multipliers and numbers, from 0 (off) to 1.
+    #[serde(default)]
+    pub adaptive: f32,
+    /// Serve the facts due for review before generating new exercises.
+    #[serde(default)]
+    pub review_due: bool,
+    #[serde(default)]
+    pub kind: Kind,
+    /// Put minus signs on the terms of missing-term problems.
+    #[serde(default)]
+    pub signed: bool,
+}
+impl Settings {
+    pub fn ranges(&self) -> Ranges {
+        self.difficulty.ranges(Some(self.custom_ranges))
+    }
+}
+
+/// Parses what the student typed; anything outside 1..limit is treated as no answer (0).
+pub fn parse_input(input_string: &str, limit: u32) -> u32 {
+    match input_string.parse() {
+        Ok(number) => {if number>0 && number<limit {number} else {0}}
+        Err(_) => {0}
+    }
+}
+
+/// Parses the answer to `blank` of `problem`, written as `Problem::notation` says with an optional minus;
+/// a percentage blank takes `75` as well as `75%`. Any other notation, or anything not below `limit` in size,
+/// is treated as no answer (zero).
+pub fn parse_answer(problem: &Problem, blank: usize, input_string: &str, limit: u32) -> Rational {
+    let input_string = input_string.trim();
+    let (number, allowed): (&str, fn(char) -> bool) = match problem.notation(blank) {
+        Notation::Whole     => {(input_string, |c| c.is_ascii_digit())}
+        Notation::Decimal   => {(input_string, |c| c.is_ascii_digit() || c == '.' || c == ',')}
+        Notation::Percent   => {(input_string.strip_suffix('%').unwrap_or(input_string).trim_end(), |c| c.is_ascii_digit() || c == '.' || c == ',')}
+    };
+    if !number.strip_prefix('-').unwrap_or(number).chars().all(allowed) {
+        return Rational::ZERO;
+    }
+    match number.parse::<Rational>() {
+        Ok(number) => {if number.abs() < Rational::from(limit) {number} else {Rational::ZERO}}
+        Err(_) => {Rational::ZERO}
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    settings: Settings,
+    seed: u64,
+    rng: ChaCha8Rng,
+    timestamp: u64,
+    started_at: Option<Instant>,
+    ended_at: Option<Instant>,
+    shown_at: Option<Instant>,
+    attempts: Vec<Attempt>,
+    /// Problems still to be answered right when practising mistakes.
+    retry: Option<VecDeque<Problem>>,
+    /// Facts due for review, served first when `Settings::review_due` is set.
+    due: VecDeque<Fact>,
+    mode: Mode,
+    error_made: bool,
+    errors_count: u32,
+    exercise_count: u32,
+    problem: Problem,
+    answers: Vec<Rational>,
+    hinted: bool,
+    weakness: Weakness,
+    /// Weaknesses from earlier sessions, added to the ones of this session by the adaptive generator.
+    prior_weakness: Weakness,
+}
+impl Default for Session {
+    fn default() -> Self {
+        Session::new(Settings::default())
+    }
+}
+impl Display for Session {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "state: {}\nerror made: {}\terror count: {}\t exercise count: {}\nproblem: {}\tanswers: {:?}\n",
+               self.mode, self.error_made, self.errors_count, self.exercise_count, self.problem, self.answers)?;
+        for (name, tally) in [("multipliers", &self.weakness.multipliers), ("numbers", &self.weakness.numbers)] {
+            write!(f, "{}:", name)?;
+            for (i, (errors, seen)) in tally.errors.iter().zip(&tally.seen).enumerate() {
+                write!(f, "\t{}: {}/{}", i+1, errors, seen)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Session {
+    pub fn new(settings: Settings) -> Self {
+        let seed = settings.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        Session{
+            settings,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            timestamp: 0,
+            started_at: None,
+            ended_at: None,
+            shown_at: None,
+            attempts: Vec::new(),
+            retry: None,
+            due: VecDeque::new(),
+            mode: Mode::default(),
+            error_made: false,
+            errors_count: 0,
+            exercise_count: 0,
+            problem: Problem::default(),
+            answers: Vec::new(),
+            hinted: false,
+            weakness: Weakness::default(),
+            prior_weakness: Weakness::default(),
+        }
+    }
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    /// Seconds since the Unix epoch when the session was started.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+    /// The problems answered wrongly, each one once.
+    pub fn mistakes(&self) -> Vec<Problem> {
+        let mut mistakes: Vec<Problem> = Vec::new();
+        for attempt in self.attempts.iter().filter(|attempt| !attempt.correct) {
+            if !mistakes.contains(&attempt.problem) {
+                mistakes.push(attempt.problem);
+            }
+        }
+        mistakes
+    }
+    pub fn is_retry(&self) -> bool {
+        self.retry.is_some()
+    }
+    /// Exercises left to fix when practising mistakes, counting the one on screen.
+    pub fn remaining_mistakes(&self) -> Option<usize> {
+        self.retry.as_ref().map(|queue| queue.len() + (self.mode == Mode::Exercising) as usize)
+    }
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+    pub fn problem(&self) -> Problem {
+        self.problem
+    }
+    pub fn answers(&self) -> &[Rational] {
+        &self.answers
+    }
+    pub fn hinted(&self) -> bool {
+        self.hinted
+    }
+    /// Records that the hint of the problem on screen was shown.
+    pub fn use_hint(&mut self) {
+        self.hinted = true;
+    }
+    pub fn error_made(&self) -> bool {
+        self.error_made
+    }
+    pub fn exercise_count(&self) -> u32 {
+        self.exercise_count
+    }
+    pub fn errors_count(&self) -> u32 {
+        self.errors_count
+    }
+    pub fn weakness(&self) -> &Weakness {
+        &self.weakness
+    }
+    pub fn set_prior_weakness(&mut self, weakness: Weakness) {
+        self.prior_weakness = weakness;
+    }
+    pub fn set_due_facts(&mut self, facts: Vec<Fact>) {
+        self.due = facts.into();
+    }
+    /// Due facts not served yet.
+    pub fn due_count(&self) -> usize {
+        if self.settings.review_due {self.due.len()} else {0}
+    }
+    /// Answers must stay below this limit, see `parse_answer`.
+    pub fn input_limit(&self) -> u32 {
+        self.settings.ranges().input_limit()
+    }
+    pub fn elapsed(&self) -> Duration {
+        match (self.started_at, self.ended_at) {
+            (Some(started_at), Some(ended_at)) => {ended_at - started_at}
+            (Some(started_at), None) => {started_at.elapsed()}
+            _ => {Duration::ZERO}
+        }
+    }
+    /// Time left in a timed session, `None` for the other lengths.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        if self.is_retry() {
+            return None;
+        }
+        match self.settings.length {
+            SessionLength::Timed(duration) => {Some(duration.saturating_sub(self.elapsed()))}
+            _ => {None}
+        }
+    }
+    pub fn is_over(&self) -> bool {
+        if let Some(remaining) = self.remaining_mistakes() {
+            return remaining == 0;
+        }
+        match self.settings.length {
+            SessionLength::Count(count) => {self.exercise_count >= count}
+            SessionLength::Unlimited => {false}
+            SessionLength::Timed(_) => {self.remaining_time() == Some(Duration::ZERO)}
+        }
+    }
+    pub fn ending(&self) -> Ending {
+        if self.is_retry() {
+            return if self.is_over() {Ending::MistakesFixed} else {Ending::Stopped};
+        }
+        match self.settings.length {
+            SessionLength::Count(count) if self.exercise_count >= count => {Ending::CountReached}
+            SessionLength::Timed(_) if self.is_over() => {Ending::TimeUp}
+            _ => {Ending::Stopped}
+        }
+    }
+    pub fn summary(&self) -> Summary {
+        Summary{
+            correct: self.exercise_count-self.errors_count,
+            errors: self.errors_count,
+            accuracy: if self.exercise_count == 0 {0.0} else {(self.exercise_count as f32 - self.errors_count as f32) / self.exercise_count as f32 * 100.0},
+            elapsed: self.elapsed(),
+            ending: self.ending(),
+        }
+    }
+
+    fn get_new_numbers (&mut self) {
+        if let Some(queue) = self.retry.as_mut() {
+            // only the mistakes are served again, and the session is over once they are fixed
+            let Some(problem) = queue.pop_front() else {
+                return;
+            };
+            // retried problems keep the signs they had
+            self.problem = if self.settings.retry.change_hidden_term {problem.with_other_hidden(&mut self.rng)} else {problem};
+            self.shown_at = Some(Instant::now());
+            return;
+        }
+        let due = if self.settings.review_due {self.due.pop_front()} else {None};
+        let problem = match due {
+            Some(fact) => {Problem::MissingTerm(fact.exercise(&mut self.rng))}
+            None if self.settings.adaptive > 0.0 => {
+                let mut weakness = self.prior_weakness.clone();
+                weakness.add(&self.weakness);
+                Problem::generate(&mut self.rng, self.settings.kind, self.settings.ranges(), &weakness, self.settings.adaptive)
+            }
+            None => {Problem::generate(&mut self.rng, self.settings.kind, self.settings.ranges(), &Weakness::default(), 0.0)}
+        };
+        self.problem = match problem {
+            Problem::MissingTerm(exercise) if self.settings.signed => {Problem::MissingTerm(exercise.with_signs(&mut self.rng))}
+            problem => {problem}
+        };
+        self.shown_at = Some(Instant::now());
+    }
+    pub fn start (&mut self) -> Problem {
+        self.timestamp = unix_time();
+        self.started_at = Some(Instant::now());
+        self.get_new_numbers();
+        self.answers.clear();
+        self.hinted = false;
+        self.mode = Mode::Exercising;
+        self.problem
+    }
+    /// Grades `answers`, one per blank of the problem, and returns whether they were correct.
+    pub fn answer (&mut self, answers: Vec<Rational>) -> bool {
+        self.answers = answers;
+        self.exercise_count += 1;
+        if self.problem.grade(&self.answers).is_err() {
+            self.errors_count += 1;
+            self.error_made = true;
+        }
+        let attempt = Attempt{
+            problem: self.problem,
+            answers: self.answers.clone(),
+            correct: !self.error_made,
+            time: self.shown_at.map(|shown_at| shown_at.elapsed()).unwrap_or_default(),
+            hinted: self.hinted,
+        };
+        self.weakness.record(&attempt);
+        self.attempts.push(attempt);
+        if let (Some(queue), true) = (self.retry.as_mut(), self.error_made) {
+            queue.push_back(self.problem);
+        }
+        self.mode = Mode::Result;
+        !self.error_made
+    }
+    #[allow(clippy::should_implement_trait)]
+    pub fn next (&mut self) -> Problem {
+        self.get_new_numbers();
+        self.answers.clear();
+        self.hinted = false;
+        self.error_made = false;
+        self.mode = Mode::Exercising;
+        self.problem
+    }
+    pub fn finish (&mut self) -> Summary {
+        self.ended_at = Some(Instant::now());
+        self.mode = Mode::FinalEvaluation;
+        self.summary()
+    }
+    /// A new session serving the mistakes of this one again, until every one of them is answered right;
+    /// `None` without mistakes.
+    pub fn retry_mistakes (&self) -> Option<Session> {
+        let mut mistakes = self.mistakes();
+        if mistakes.is_empty() {
+            return None;
+        }
+        let mut session = Session::new(self.settings);
+        if self.settings.retry.shuffle {
+            mistakes.shuffle(&mut session.rng);
+        }
+        session.retry = Some(mistakes.into());
+        Some(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::DecimalForm;
+
+    fn session(length: SessionLength) -> Session {
+        Session::new(Settings{seed: Some(7), length, ..Settings::default()})
+    }
+
+    /// `2/3 = 8/12` with term `input_field_number` hidden.
+    fn exercise(input_field_number: u32) -> Exercise {
+        Exercise{num_a: 2, den_a: 3, num_b: 8, den_b: 12, input_field_number, negative: [false; 4]}
+    }
+
+    /// Puts `exercise` on screen instead of the generated problem.
+    fn show(session: &mut Session, exercise: Exercise) {
+        session.problem = Problem::MissingTerm(exercise);
+    }
+
+    #[test]
+    fn each_blank_takes_its_own_notation() {
+        let problem = Problem::Decimal(DecimalForm{fraction: Fraction::new(3, 4), power: 100});
+        assert_eq!(parse_answer(&problem, 0, "75", 1000), Rational::whole(75));
+        assert_eq!(parse_answer(&problem, 0, "1000", 1000), Rational::ZERO);
+        assert_eq!(parse_answer(&problem, 1, "0.75", 1000), Rational::new(3, 4));
+        assert_eq!(parse_answer(&problem, 1, "0,75", 1000), Rational::new(3, 4));
+        assert_eq!(parse_answer(&problem, 1, "75%", 1000), Rational::ZERO);
+        assert_eq!(parse_answer(&problem, 1, "3/4", 1000), Rational::ZERO);
+        assert_eq!(parse_answer(&problem, 2, "75%", 1000), Rational::whole(75));
+        assert_eq!(parse_answer(&problem, 2, "75", 1000), Rational::whole(75));
+        assert_eq!(parse_answer(&problem, 2, "12.5 %", 1000), Rational::new(25, 2));
+        assert_eq!(parse_answer(&problem, 2, "3/4", 1000), Rational::ZERO);
+
+        let missing = Problem::MissingTerm(exercise(2));
+        assert_eq!(parse_answer(&missing, 0, "8", 1000), Rational::whole(8));
+        assert_eq!(parse_answer(&missing, 0, " -8 ", 1000), Rational::whole(-8));
+        assert_eq!(parse_answer(&missing, 0, "800%", 1000), Rational::ZERO);
+        assert_eq!(parse_answer(&missing, 0, "16/2", 1000), Rational::ZERO);
+        assert_eq!(parse_answer(&missing, 0, "8.0", 1000), Rational::ZERO);
+    }
+
+    #[test]
+    fn grades_every_hidden_term() {
+        for (field, solution) in [(0, 2), (1, 3), (2, 8), (3, 12)] {
+            let mut session = session(SessionLength::Count(2));
+            session.start();
+            show(&mut session, exercise(field));
+            assert!(session.answer(vec![Rational::whole(solution)]), "field {}", field);
+            assert_eq!(session.mode(), Mode::Result);
+            assert!(!session.error_made());
+
+            session.next();
+            show(&mut session, exercise(field));
+            assert!(!session.answer(vec![Rational::whole(solution + 1)]), "field {}", field);
+            assert!(session.error_made());
+
+            let summary = session.finish();
+            assert_eq!(session.mode(), Mode::FinalEvaluation);
+            assert_eq!((summary.correct, summary.errors, summary.accuracy), (1, 1, 50.0));
+            assert_eq!(session.attempts().len(), 2);
+            assert!(session.attempts()[0].correct && !session.attempts()[1].correct);
+        }
+    }
+
+    #[test]
+    fn next_clears_the_problem_on_screen() {
+        let mut session = session(SessionLength::Count(3));
+        assert_eq!(session.mode(), Mode::Start);
+        session.start();
+        assert_eq!(session.mode(), Mode::Exercising);
+        show(&mut session, exercise(0));
+        session.answer(vec![Rational::whole(5)]);
+        session.next();
+        assert_eq!(session.mode(), Mode::Exercising);
+        assert!(session.answers().is_empty());
+        assert!(!session.error_made());
+        assert_eq!((session.exercise_count(), session.errors_count()), (1, 1));
+    }
+
+    #[test]
+    fn count_sessions_end_after_the_count() {
+        let mut session = session(SessionLength::Count(2));
+        session.start();
+        for _ in 0..2 {
+            assert!(!session.is_over());
+            assert_eq!(session.ending(), Ending::Stopped);
+            let solution = session.problem().solution();
+            assert!(session.answer(solution));
+            session.next();
+        }
+        assert!(session.is_over());
+        assert_eq!(session.finish().ending, Ending::CountReached);
+    }
+
+    #[test]
+    fn unlimited_sessions_end_when_stopped() {
+        let mut session = session(SessionLength::Unlimited);
+        session.start();
+        for _ in 0..50 {
+            let solution = session.problem().solution();
+            session.answer(solution);
+            assert!(!session.is_over());
+            session.next();
+        }
+        assert_eq!(session.remaining_time(), None);
+        assert_eq!(session.finish().ending, Ending::Stopped);
+    }
+
+    #[test]
+    fn timed_sessions_end_when_the_time_is_up() {
+        let mut running = session(SessionLength::Timed(Duration::from_secs(3600)));
+        running.start();
+        assert!(!running.is_over());
+        assert!(running.remaining_time().is_some_and(|remaining| remaining > Duration::ZERO));
+        assert_eq!(running.finish().ending, Ending::Stopped);
+
+        let mut elapsed = session(SessionLength::Timed(Duration::ZERO));
+        elapsed.start();
+        assert!(elapsed.is_over());
+        assert_eq!(elapsed.remaining_time(), Some(Duration::ZERO));
+        assert_eq!(elapsed.finish().ending, Ending::TimeUp);
+    }
+
+    #[test]
+    fn retrying_serves_only_the_mistakes() {
+        let mut session = session(SessionLength::Count(3));
+        session.start();
+        assert!(session.retry_mistakes().is_none());
+        show(&mut session, exercise(0));
+        session.answer(vec![Rational::whole(5)]);
+        session.next();
+        show(&mut session, exercise(3));
+        session.answer(vec![Rational::whole(12)]);
+        session.next();
+        session.answer(vec![Rational::ZERO]);
+        session.finish();
+
+        let mut retry = session.retry_mistakes().unwrap();
+        let mistakes = session.mistakes();
+        assert_eq!(mistakes.len(), 2);
+        assert_eq!(retry.start(), mistakes[0]);
+        assert_eq!(retry.remaining_mistakes(), Some(2));
+        let solution = retry.problem().solution();
+        assert!(retry.answer(solution));
+        assert_eq!(retry.next(), mistakes[1]);
+        // a wrong answer puts the problem back in the queue
+        assert!(!retry.answer(vec![Rational::ZERO]));
+        assert_eq!(retry.next(), mistakes[1]);
+        let solution = retry.problem().solution();
+        assert!(retry.answer(solution));
+        assert!(retry.is_over());
+        assert_eq!(retry.remaining_mistakes(), Some(0));
+        // nothing new is generated once the mistakes are fixed
+        assert_eq!(retry.next(), mistakes[1]);
+        assert_eq!(retry.finish().ending, Ending::MistakesFixed);
+    }
+
+    #[test]
+    fn the_same_seed_replays_the_same_problems() {
+        let (mut first, mut second) = (session(SessionLength::Count(10)), session(SessionLength::Count(10)));
+        assert_eq!(first.start(), second.start());
+        for _ in 0..9 {
+            assert_eq!(first.next(), second.next());
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt::Display;
+use iced::{Color, Element, Length, Padding, Alignment, Renderer, Theme};
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::{button, text, tooltip, Button, Column, Container, Row, Text, Tooltip};
+use propinva::engine::Exercise;
+use propinva::problem::{relation_symbol, Problem};
+use propinva::rational::Rational;
+use crate::Message;
+use crate::styles::LineFakeButton;
+
+pub fn term(value: impl Display) -> Text<'static, Theme, Renderer> {
+    text(value.to_string()).size(46)
+}
+
+/// A term coloured green or red depending on whether it was guessed.
+pub fn graded_term(value: impl Display, correct: bool) -> Text<'static, Theme, Renderer> {
+    if correct {
+        term(value).style(Color::from_rgb(0.4, 0.8, 0.0))
+    } else {
+        term(value).style(Color::from_rgb(0.9, 0.35, 0.26))
+    }
+}
+
+/// A relation sign between two fractions.
+pub fn sign(symbol: &str) -> Text<'static, Theme, Renderer> {
+    text(symbol).size(50)
+}
+
+/// The blanks of `problem` filled with their solution, graded, each showing what was typed when hovered.
+pub fn graded_blanks(problem: &Problem, answers: &[Rational]) -> Vec<Element<'static, Message, Theme, Renderer>> {
+    // above numerators, below denominators
+    let positions = match problem {
+        Problem::MissingTerm(exercise) => {
+            match exercise.input_field_number {
+                0 | 2 => {vec![tooltip::Position::Top]}
+                _ => {vec![tooltip::Position::Bottom]}
+            }
+        }
+        Problem::Simplify(_) | Problem::Multiplier(_) => {vec![tooltip::Position::Top, tooltip::Position::Bottom]}
+        Problem::Equivalent(equivalence) => {
+            // the verdict takes the place of the sign
+            let symbol = if equivalence.is_equivalent() {"="} else {"≠"};
+            let inserted = text(format!("Inserted: {}", problem.format_answers(answers))).size(30).style(Color::from_rgb(0.6, 0.8, 1.0));
+            let color = if problem.grade(answers).is_ok() {Color::from_rgb(0.4, 0.8, 0.0)} else {Color::from_rgb(0.9, 0.35, 0.26)};
+            return vec![Tooltip::new(sign(symbol).style(color), inserted, tooltip::Position::Top).into()];
+        }
+        Problem::MixedNumber(conversion) if conversion.to_improper => {vec![tooltip::Position::Top]}
+        Problem::MixedNumber(_) => {vec![tooltip::Position::Left, tooltip::Position::Top]}
+        Problem::Decimal(_) => {vec![tooltip::Position::Top, tooltip::Position::Bottom, tooltip::Position::Bottom]}
+        Problem::Chain(chain) => {
+            chain.hidden_numerators[1..chain.len].iter()
+                .map(|numerator| if *numerator {tooltip::Position::Top} else {tooltip::Position::Bottom})
+                .collect()
+        }
+        Problem::Compare(_) => {
+            let grades = problem.grade_blanks(answers);
+            return problem.solution().into_iter().zip(grades).enumerate()
+                .map(|(gap, (solution, correct))| {
+                    let inserted = text(format!("Inserted: {}", relation_symbol(answers.get(gap).and_then(Rational::to_u32).unwrap_or(0)))).size(30).style(Color::from_rgb(0.6, 0.8, 1.0));
+                    let color = if correct {Color::from_rgb(0.4, 0.8, 0.0)} else {Color::from_rgb(0.9, 0.35, 0.26)};
+                    Tooltip::new(sign(relation_symbol(solution.to_u32().unwrap_or(0))).style(color), inserted, tooltip::Position::Top).into()
+                })
+                .collect();
+        }
+    };
+    let grades = problem.grade_blanks(answers);
+    problem.solution().into_iter().zip(positions).zip(grades).enumerate()
+        .map(|(blank, ((solution, position), correct))| {
+            let inserted = text(format!("Inserted: {}", answers.get(blank).copied().unwrap_or_default())).size(30).style(Color::from_rgb(0.6, 0.8, 1.0));
+            Tooltip::new(graded_term(solution, correct), inserted, position).into()
+        })
+        .collect()
+}
+
+/// `numerator` over `denominator`, separated by a line `width` wide.
+pub fn fraction<'a>(numerator: Element<'a, Message, Theme, Renderer>, denominator: Element<'a, Message, Theme, Renderer>, width: f32) -> Column<'a, Message, Theme, Renderer> {
+    let line: Button<'_, Message, Theme, Renderer> = button("")
+        .style(LineFakeButton::new())
+        .width(Length::Fixed(width))
+        .height(Length::Fixed(2.0));
+
+    Column::new()
+        .push(numerator)
+        .push(Container::new(line).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
+        .push(denominator)
+        .align_items(Alignment::Center)
+}
+
+/// `whole` next to the fraction `numerator` over `denominator`.
+pub fn mixed_number<'a>(whole: Element<'a, Message, Theme, Renderer>, numerator: Element<'a, Message, Theme, Renderer>, denominator: Element<'a, Message, Theme, Renderer>, width: f32) -> Column<'a, Message, Theme, Renderer> {
+    Column::new().push(Row::new()
+        .push(whole)
+        .push(fraction(numerator, denominator, width))
+        .spacing(10)
+        .align_items(Alignment::Center)
+    )
+}
+
+/// Lays out `left sign right` filling the available space.
+pub fn equation<'a>(left: Column<'a, Message, Theme, Renderer>, sign: Element<'a, Message, Theme, Renderer>, right: Column<'a, Message, Theme, Renderer>) -> Container<'a, Message, Theme, Renderer> {
+    Container::new(Row::new()
+        .push(Container::new(left).height(Length::Fill)
+            .align_y(Vertical::Center)
+            .align_x(Horizontal::Right)
+        )
+        .push(Container::new(sign)
+            .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+        )
+        .push(Container::new(right).height(Length::Fill)
+            .align_y(Vertical::Center)
+            .align_x(Horizontal::Left)
+        ))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+}
+
+/// Lays out `fractions` with `signs` between them, tighter than `equation` to fit four or five of them.
+pub fn chain<'a>(fractions: Vec<Column<'a, Message, Theme, Renderer>>, signs: Vec<Element<'a, Message, Theme, Renderer>>) -> Container<'a, Message, Theme, Renderer> {
+    let mut row = Row::new().align_items(Alignment::Center);
+    let mut signs = signs.into_iter();
+    for (index, fraction) in fractions.into_iter().enumerate() {
+        if index > 0 {
+            let sign = signs.next().unwrap_or_else(|| sign("?").into());
+            row = row.push(Container::new(sign).padding(Padding{top: 0.0, right: 12.0, bottom: 0.0, left: 12.0}));
+        }
+        row = row.push(fraction);
+    }
+    Container::new(row)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+}
+
+/// Lays out `num_a/den_a = num_b/den_b` filling the available space, with `hidden` in place of the hidden term.
+pub fn fractions<'a>(exercise: &Exercise, hidden: Element<'a, Message, Theme, Renderer>) -> Container<'a, Message, Theme, Renderer> {
+    let mut hidden = Some(hidden);
+    let mut cell = |field: u32| -> Element<'a, Message, Theme, Renderer> {
+        let shown = if field == exercise.input_field_number {hidden.take()} else {None};
+        shown.unwrap_or_else(|| term(exercise.signed_term(field)).into())
+    };
+    let numerator_a = cell(0);
+    let denominator_a = cell(1);
+    let numerator_b = cell(2);
+    let denominator_b = cell(3);
+
+    equation(fraction(numerator_a, denominator_a, 120.0), sign("=").into(), fraction(numerator_b, denominator_b, 100.0))
+}
+
+/// Lays out `problem` filling the available space, with `blanks` in place of the terms or signs to find
+/// and the fractions over a common denominator below them when `hint` is set.
+pub fn problem_view<'a>(problem: &Problem, blanks: Vec<Element<'a, Message, Theme, Renderer>>, hint: bool) -> Container<'a, Message, Theme, Renderer> {
+    let mut blanks = blanks.into_iter();
+    let mut blank = || blanks.next().unwrap_or_else(|| text("?").size(46).into());
+    match problem {
+        Problem::MissingTerm(exercise) => {fractions(exercise, blank())}
+        Problem::Simplify(shown) => {
+            let (numerator, denominator) = (blank(), blank());
+            equation(fraction(term(shown.num).into(), term(shown.den).into(), 120.0), sign("=").into(), fraction(numerator, denominator, 100.0))
+        }
+        Problem::Equivalent(equivalence) => {
+            let verdict = blanks.next().unwrap_or_else(|| sign("?").into());
+            equation(fraction(term(equivalence.a.num).into(), term(equivalence.a.den).into(), 120.0), verdict, fraction(term(equivalence.b.num).into(), term(equivalence.b.den).into(), 100.0))
+        }
+        Problem::Chain(shown) => {
+            let fractions = shown.fractions().iter().enumerate()
+                .map(|(index, value)| {
+                    let numerator = if index > 0 && shown.hidden_numerators[index] {blank()} else {term(value.num).into()};
+                    let denominator = if index > 0 && !shown.hidden_numerators[index] {blank()} else {term(value.den).into()};
+                    fraction(numerator, denominator, 90.0)
+                })
+                .collect();
+            let signs = (1..shown.len).map(|_| sign("=").into()).collect();
+            chain(fractions, signs)
+        }
+        Problem::Compare(comparison) => {
+            let common = comparison.common_denominator();
+            let fractions = comparison.fractions().iter().zip(comparison.hint())
+                .map(|(value, (numerator, multiplier))| {
+                    let column = fraction(term(value.num).into(), term(value.den).into(), 70.0);
+                    if hint {
+                        column.push(text(format!("×{}\n{}/{}", multiplier, numerator, common)).size(15).horizontal_alignment(Horizontal::Center))
+                    } else {
+                        column
+                    }
+                })
+                .collect();
+            chain(fractions, blanks.collect())
+        }
+        Problem::MixedNumber(conversion) => {
+            let (mixed, improper) = (conversion.mixed, conversion.improper());
+            if conversion.to_improper {
+                let left = mixed_number(term(mixed.whole).into(), term(mixed.fraction.num).into(), term(mixed.fraction.den).into(), 80.0);
+                equation(left, sign("=").into(), fraction(blank(), term(improper.den).into(), 100.0))
+            } else {
+                let (whole, numerator) = (blank(), blank());
+                let right = mixed_number(whole, numerator, term(mixed.fraction.den).into(), 100.0);
+                equation(fraction(term(improper.num).into(), term(improper.den).into(), 120.0), sign("=").into(), right)
+            }
+        }
+        Problem::Multiplier(scaling) => {
+            let factor: Element<'a, Message, Theme, Renderer> = if problem.blanks() == 1 {
+                blank()
+            } else {
+                let (numerator, denominator) = (blank(), blank());
+                fraction(numerator, denominator, 100.0).into()
+            };
+            Container::new(Column::new()
+                .push(equation(fraction(term(scaling.from.num).into(), term(scaling.from.den).into(), 120.0), sign("=").into(), fraction(term(scaling.to.num).into(), term(scaling.to.den).into(), 100.0)))
+                .push(Row::new()
+                    .push(text("multiplied by").size(30))
+                    .push(factor)
+                    .spacing(20)
+                    .align_items(Alignment::Center)
+                )
+                .align_items(Alignment::Center)
+            ).width(Length::Fill)
+            .height(Length::Fill)
+        }
+        Problem::Decimal(form) => {
+            let (numerator, decimal, percent) = (blank(), blank(), blank());
+            let fractions = vec![
+                fraction(term(form.fraction.num).into(), term(form.fraction.den).into(), 90.0),
+                fraction(numerator, term(form.power).into(), 90.0),
+                Column::new().push(decimal),
+                Column::new().push(Row::new().push(percent).push(term("%")).spacing(5).align_items(Alignment::Center)),
+            ];
+            chain(fractions, (0..3).map(|_| sign("=").into()).collect())
+        }
+    }
+}
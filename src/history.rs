@@ -0,0 +1,386 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::engine::{Attempt, Ending, Session, Settings};
+use crate::schedule::Schedule;
+
+/// Version written to new history files, bump it together with a new entry in `MIGRATIONS`.
+pub const FORMAT_VERSION: u32 = 3;
+
+/// `MIGRATIONS[n]` upgrades a file from version `n+1` to version `n+2`.
+const MIGRATIONS: &[fn(Value) -> Value] = &[migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// Profile that gets the sessions of files written before profiles existed.
+pub const DEFAULT_PROFILE: &str = "Student";
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    MissingVersion,
+    UnsupportedVersion(u32),
+    EmptyName,
+    DuplicateProfile(String),
+    UnknownProfile(String),
+}
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryError::Io(error)                     => {write!(f, "cannot access the history file: {}", error)},
+            HistoryError::Json(error)                   => {write!(f, "malformed history file: {}", error)},
+            HistoryError::MissingVersion                => {write!(f, "malformed history file: missing version")},
+            HistoryError::UnsupportedVersion(version)   => {write!(f, "history file version {} is newer than the supported version {}", version, FORMAT_VERSION)},
+            HistoryError::EmptyName                     => {write!(f, "the profile name is empty")},
+            HistoryError::DuplicateProfile(name)        => {write!(f, "a profile named {} already exists", name)},
+            HistoryError::UnknownProfile(name)          => {write!(f, "there is no profile named {}", name)},
+        }
+    }
+}
+impl std::error::Error for HistoryError {}
+impl From<io::Error> for HistoryError {
+    fn from(error: io::Error) -> Self {
+        HistoryError::Io(error)
+    }
+}
+impl From<serde_json::Error> for HistoryError {
+    fn from(error: serde_json::Error) -> Self {
+        HistoryError::Json(error)
+    }
+}
+
+/// Everything worth keeping about a finished session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub timestamp: u64,
+    pub seed: u64,
+    pub settings: Settings,
+    pub attempts: Vec<Attempt>,
+    pub elapsed: Duration,
+    pub ending: Ending,
+}
+impl From<&Session> for SessionRecord {
+    fn from(session: &Session) -> Self {
+        let summary = session.summary();
+        SessionRecord{
+            timestamp: session.timestamp(),
+            seed: session.seed(),
+            settings: session.settings(),
+            attempts: session.attempts().to_vec(),
+            elapsed: summary.elapsed,
+            ending: summary.ending,
+        }
+    }
+}
+
+/// A student, with the settings they last used and every session they finished.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub settings: Settings,
+    pub sessions: Vec<SessionRecord>,
+    #[serde(default)]
+    pub schedule: Schedule,
+}
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Profile{
+            name: String::from(name),
+            settings: Settings::default(),
+            sessions: Vec::new(),
+            schedule: Schedule::default(),
+        }
+    }
+    /// Stores a finished session and reschedules the facts it reviewed.
+    pub fn add_session(&mut self, record: SessionRecord) {
+        self.schedule.review_attempts(&record.attempts, record.timestamp + record.elapsed.as_secs());
+        self.sessions.push(record);
+    }
+    pub fn attempts(&self) -> impl Iterator<Item = &Attempt> {
+        self.sessions.iter().flat_map(|session| session.attempts.iter())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub version: u32,
+    pub profiles: Vec<Profile>,
+    /// The profile picked last time, preselected on the next launch.
+    pub selected: Option<String>,
+}
+impl Default for History {
+    fn default() -> Self {
+        History{
+            version: FORMAT_VERSION,
+            profiles: Vec::new(),
+            selected: None,
+        }
+    }
+}
+
+impl History {
+    /// `history.json` in the `propinva` folder of the platform data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|data_dir| data_dir.join("propinva").join("history.json"))
+    }
+    /// Reads the history at `path`, upgrading older formats; a missing file is an empty history.
+    pub fn load(path: &Path) -> Result<Self, HistoryError> {
+        if !path.exists() {
+            return Ok(History::default());
+        }
+        let value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(serde_json::from_value(migrate(value)?)?)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), HistoryError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // write next to the real file and rename, so a crash never leaves half a history behind
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.iter().map(|profile| profile.name.clone()).collect()
+    }
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+    pub fn profile_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|profile| profile.name == name)
+    }
+    pub fn profile_or_insert(&mut self, name: &str) -> &mut Profile {
+        match self.profiles.iter().position(|profile| profile.name == name) {
+            Some(index) => {&mut self.profiles[index]}
+            None => {
+                self.profiles.push(Profile::new(name));
+                self.profiles.last_mut().unwrap()
+            }
+        }
+    }
+    pub fn add_profile(&mut self, name: &str) -> Result<&mut Profile, HistoryError> {
+        let name = self.check_new_name(name)?;
+        self.profiles.push(Profile::new(&name));
+        Ok(self.profiles.last_mut().unwrap())
+    }
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<(), HistoryError> {
+        let new_name = self.check_new_name(new_name)?;
+        let profile = self.profile_mut(name).ok_or(HistoryError::UnknownProfile(String::from(name)))?;
+        profile.name = new_name.clone();
+        if self.selected.as_deref() == Some(name) {
+            self.selected = Some(new_name);
+        }
+        Ok(())
+    }
+    pub fn remove_profile(&mut self, name: &str) -> Result<Profile, HistoryError> {
+        let index = self.profiles.iter().position(|profile| profile.name == name).ok_or(HistoryError::UnknownProfile(String::from(name)))?;
+        if self.selected.as_deref() == Some(name) {
+            self.selected = None;
+        }
+        Ok(self.profiles.remove(index))
+    }
+    fn check_new_name(&self, name: &str) -> Result<String, HistoryError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(HistoryError::EmptyName);
+        }
+        if self.profile(name).is_some() {
+            return Err(HistoryError::DuplicateProfile(String::from(name)));
+        }
+        Ok(String::from(name))
+    }
+}
+
+fn migrate(mut value: Value) -> Result<Value, HistoryError> {
+    let version = value.get("version").and_then(Value::as_u64).ok_or(HistoryError::MissingVersion)? as u32;
+    if version == 0 {
+        return Err(HistoryError::MissingVersion);
+    }
+    if version > FORMAT_VERSION {
+        return Err(HistoryError::UnsupportedVersion(version));
+    }
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        value = migration(value);
+    }
+    value["version"] = Value::from(FORMAT_VERSION);
+    Ok(value)
+}
+
+/// Version 1 kept a single list of sessions, they now belong to the default profile.
+fn migrate_v1_to_v2(mut value: Value) -> Value {
+    let sessions = value["sessions"].take();
+    let settings = sessions.as_array()
+        .and_then(|sessions| sessions.last())
+        .map(|session| session["settings"].clone())
+        .unwrap_or_else(|| serde_json::to_value(Settings::default()).unwrap());
+    let mut profiles = Vec::new();
+    if sessions.as_array().is_some_and(|sessions| !sessions.is_empty()) {
+        profiles.push(serde_json::json!({"name": DEFAULT_PROFILE, "settings": settings, "sessions": sessions}));
+    }
+    serde_json::json!({"version": 2, "profiles": profiles, "selected": null})
+}
+
+/// Version 2 only knew missing-term exercises with a single answer, attempts now hold any problem.
+fn migrate_v2_to_v3(mut value: Value) -> Value {
+    let profiles = value["profiles"].as_array_mut().into_iter().flatten();
+    let sessions = profiles.flat_map(|profile| profile["sessions"].as_array_mut().into_iter().flatten());
+    for session in sessions {
+        for attempt in session["attempts"].as_array_mut().into_iter().flatten() {
+            let exercise = attempt["exercise"].take();
+            let answer = attempt["answer"].take();
+            if let Some(attempt) = attempt.as_object_mut() {
+                attempt.remove("exercise");
+                attempt.remove("answer");
+                attempt.insert(String::from("problem"), serde_json::json!({"MissingTerm": exercise}));
+                attempt.insert(String::from("answers"), serde_json::json!([answer]));
+            }
+        }
+    }
+    value["version"] = Value::from(3);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Exercise, SessionLength};
+    use crate::problem::Problem;
+    use crate::rational::Rational;
+
+    const V1: &str = r#"{
+        "version": 1,
+        "sessions": [{
+            "timestamp": 1700000000,
+            "seed": 42,
+            "settings": {
+                "seed": null,
+                "length": {"Count": 2},
+                "difficulty": "Easy",
+                "custom_ranges": {"multiplier": {"min": 2, "max": 10}, "number": {"min": 1, "max": 12}}
+            },
+            "attempts": [
+                {"exercise": {"num_a": 2, "den_a": 3, "num_b": 8, "den_b": 12, "input_field_number": 2}, "answer": 8, "correct": true, "time": {"secs": 3, "nanos": 0}},
+                {"exercise": {"num_a": 5, "den_a": 4, "num_b": 15, "den_b": 12, "input_field_number": 1}, "answer": 3, "correct": false, "time": {"secs": 5, "nanos": 0}}
+            ],
+            "elapsed": {"secs": 8, "nanos": 0},
+            "ending": "CountReached"
+        }]
+    }"#;
+
+    const V2: &str = r#"{
+        "version": 2,
+        "profiles": [{
+            "name": "Ada",
+            "settings": {
+                "seed": null,
+                "length": "Unlimited",
+                "difficulty": "Medium",
+                "custom_ranges": {"multiplier": {"min": 2, "max": 10}, "number": {"min": 1, "max": 12}},
+                "adaptive": 0.5,
+                "review_due": true
+            },
+            "sessions": [{
+                "timestamp": 1700000100,
+                "seed": 7,
+                "settings": {
+                    "seed": null,
+                    "length": "Unlimited",
+                    "difficulty": "Medium",
+                    "custom_ranges": {"multiplier": {"min": 2, "max": 10}, "number": {"min": 1, "max": 12}}
+                },
+                "attempts": [
+                    {"exercise": {"num_a": 6, "den_a": 9, "num_b": 2, "den_b": 3, "input_field_number": 0}, "answer": 4, "correct": false, "time": {"secs": 2, "nanos": 500000000}}
+                ],
+                "elapsed": {"secs": 2, "nanos": 500000000},
+                "ending": "Stopped"
+            }]
+        }],
+        "selected": "Ada"
+    }"#;
+
+    /// Loads `text` the way a history file on disk is loaded.
+    fn load(name: &str, text: &str) -> Result<History, HistoryError> {
+        let path = std::env::temp_dir().join(format!("propinva-{}-{}.json", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        let history = History::load(&path);
+        fs::remove_file(&path).unwrap();
+        history
+    }
+
+    fn exercise(num_a: u32, den_a: u32, num_b: u32, den_b: u32, input_field_number: u32) -> Problem {
+        Problem::MissingTerm(Exercise{num_a, den_a, num_b, den_b, input_field_number, negative: [false; 4]})
+    }
+
+    #[test]
+    fn migrates_version_1_into_the_default_profile() {
+        let history = load("v1", V1).unwrap();
+        assert_eq!(history.version, FORMAT_VERSION);
+        assert_eq!(history.names(), vec![String::from(DEFAULT_PROFILE)]);
+        assert_eq!(history.selected, None);
+
+        let profile = history.profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(profile.settings.length, SessionLength::Count(2));
+        assert_eq!(profile.sessions.len(), 1);
+        let session = &profile.sessions[0];
+        assert_eq!((session.timestamp, session.seed, session.ending), (1700000000, 42, Ending::CountReached));
+        assert_eq!(session.attempts.len(), 2);
+        assert_eq!(session.attempts[0].problem, exercise(2, 3, 8, 12, 2));
+        assert_eq!(session.attempts[0].answers, vec![Rational::whole(8)]);
+        assert!(session.attempts[0].correct);
+        assert_eq!(session.attempts[1].problem, exercise(5, 4, 15, 12, 1));
+        assert_eq!(session.attempts[1].answers, vec![Rational::whole(3)]);
+        assert!(!session.attempts[1].correct);
+        assert_eq!(session.attempts[1].time, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn migrates_version_2_attempts_to_problems() {
+        let history = load("v2", V2).unwrap();
+        assert_eq!(history.version, FORMAT_VERSION);
+        assert_eq!(history.selected.as_deref(), Some("Ada"));
+
+        let profile = history.profile("Ada").unwrap();
+        assert_eq!(profile.settings.adaptive, 0.5);
+        assert!(profile.settings.review_due);
+        let attempt = &profile.sessions[0].attempts[0];
+        assert_eq!(attempt.problem, exercise(6, 9, 2, 3, 0));
+        assert_eq!(attempt.answers, vec![Rational::whole(4)]);
+        assert_eq!(attempt.time, Duration::from_millis(2500));
+        assert!(!attempt.hinted);
+    }
+
+    #[test]
+    fn an_empty_version_1_file_has_no_profiles() {
+        let history = load("v1-empty", r#"{"version": 1, "sessions": []}"#).unwrap();
+        assert!(history.profiles.is_empty());
+    }
+
+    #[test]
+    fn saved_histories_load_unchanged() {
+        let history = load("v2-saved", V2).unwrap();
+        let saved = serde_json::to_string(&history).unwrap();
+        assert_eq!(load("v3", &saved).unwrap(), history);
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let newer = format!(r#"{{"version": {}, "profiles": [], "selected": null}}"#, FORMAT_VERSION + 1);
+        assert!(matches!(load("newer", &newer), Err(HistoryError::UnsupportedVersion(version)) if version == FORMAT_VERSION + 1));
+    }
+
+    #[test]
+    fn refuses_files_without_a_version() {
+        assert!(matches!(load("unversioned", r#"{"sessions": []}"#), Err(HistoryError::MissingVersion)));
+        assert!(matches!(load("version-0", r#"{"version": 0, "sessions": []}"#), Err(HistoryError::MissingVersion)));
+    }
+
+    #[test]
+    fn a_missing_file_is_an_empty_history() {
+        let path = std::env::temp_dir().join(format!("propinva-{}-missing.json", std::process::id()));
+        assert_eq!(History::load(&path).unwrap(), History::default());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::problem::{relation_symbol, Problem};
+
+/// A term or sign of a problem as laid out outside the window: shown, or a blank holding its solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Given(String),
+    Blank{index: usize, solution: String},
+}
+
+/// One column of a laid out problem: a fraction over a line, or a term on the line's row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Fraction(Term, Term),
+    Text(Term),
+}
+
+fn given(value: impl ToString) -> Term {
+    Term::Given(value.to_string())
+}
+
+fn fraction(numerator: impl ToString, denominator: impl ToString) -> Cell {
+    Cell::Fraction(given(numerator), given(denominator))
+}
+
+/// The cells of `problem` from left to right, its blanks numbered in the order the answers are given.
+pub fn cells(problem: &Problem) -> Vec<Cell> {
+    let mut solutions = match problem {
+        Problem::Equivalent(equivalence) => {vec![String::from(if equivalence.is_equivalent() {"="} else {"≠"})]}
+        Problem::Compare(_) => {problem.solution().iter().map(|answer| String::from(relation_symbol(answer.to_u32().unwrap_or(0)))).collect()}
+        _ => {problem.solution().iter().map(|answer| answer.to_string()).collect()}
+    }.into_iter().enumerate();
+    let mut blank = || {
+        let (index, solution) = solutions.next().unwrap_or((0, String::from("?")));
+        Term::Blank{index, solution}
+    };
+    let equals = || Cell::Text(given("="));
+    match problem {
+        Problem::MissingTerm(exercise) => {
+            let mut term = |field: u32| if field == exercise.input_field_number {blank()} else {given(exercise.signed_term(field))};
+            let (numerator_a, denominator_a) = (term(0), term(1));
+            let (numerator_b, denominator_b) = (term(2), term(3));
+            vec![Cell::Fraction(numerator_a, denominator_a), equals(), Cell::Fraction(numerator_b, denominator_b)]
+        }
+        Problem::Simplify(shown) => {
+            let (numerator, denominator) = (blank(), blank());
+            vec![fraction(shown.num, shown.den), equals(), Cell::Fraction(numerator, denominator), Cell::Text(given("in lowest terms"))]
+        }
+        Problem::Equivalent(equivalence) => {
+            vec![fraction(equivalence.a.num, equivalence.a.den), Cell::Text(blank()), fraction(equivalence.b.num, equivalence.b.den)]
+        }
+        Problem::Chain(chain) => {
+            let mut cells = Vec::new();
+            for (index, value) in chain.fractions().iter().enumerate() {
+                if index > 0 {
+                    cells.push(equals());
+                }
+                let numerator = if index > 0 && chain.hidden_numerators[index] {blank()} else {given(value.num)};
+                let denominator = if index > 0 && !chain.hidden_numerators[index] {blank()} else {given(value.den)};
+                cells.push(Cell::Fraction(numerator, denominator));
+            }
+            cells
+        }
+        Problem::Compare(comparison) => {
+            let mut cells = Vec::new();
+            for (index, value) in comparison.fractions().iter().enumerate() {
+                if index > 0 {
+                    cells.push(Cell::Text(blank()));
+                }
+                cells.push(fraction(value.num, value.den));
+            }
+            cells
+        }
+        Problem::Multiplier(scaling) => {
+            let factor = if problem.blanks() == 1 {
+                Cell::Text(blank())
+            } else {
+                let (numerator, denominator) = (blank(), blank());
+                Cell::Fraction(numerator, denominator)
+            };
+            vec![fraction(scaling.from.num, scaling.from.den), Cell::Text(given("×")), factor, equals(), fraction(scaling.to.num, scaling.to.den)]
+        }
+        Problem::MixedNumber(conversion) => {
+            let (mixed, improper) = (conversion.mixed, conversion.improper());
+            if conversion.to_improper {
+                vec![Cell::Text(given(mixed.whole)), fraction(mixed.fraction.num, mixed.fraction.den), equals(), Cell::Fraction(blank(), given(improper.den))]
+            } else {
+                let (whole, numerator) = (blank(), blank());
+                vec![fraction(improper.num, improper.den), equals(), Cell::Text(whole), Cell::Fraction(numerator, given(mixed.fraction.den))]
+            }
+        }
+        Problem::Decimal(form) => {
+            let (numerator, decimal, percent) = (blank(), blank(), blank());
+            vec![fraction(form.fraction.num, form.fraction.den), equals(), Cell::Fraction(numerator, given(form.power)), equals(), Cell::Text(decimal), equals(), Cell::Text(percent), Cell::Text(given("%"))]
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub mod difficulty;
+pub mod engine;
+pub mod history;
+pub mod layout;
+pub mod problem;
+pub mod progress;
+pub mod rational;
+pub mod report;
+pub mod schedule;
+pub mod weakness;
+pub mod worksheet;
@@ -2,214 +2,388 @@
 
 mod styles;
 mod buttons;
+mod cli;
+mod fractions;
+mod store;
+mod tui;
+mod batch;
 
-use std::cmp::PartialEq;
 use std::fmt::{Display, Formatter};
-use iced::{Element, Renderer, Application, Settings, Theme, Command, window, executor, Size, Length, Padding, Alignment, Color};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use iced::{Element, Renderer, Application, Settings, Theme, Command, Subscription, window, executor, time, Size, Length, Padding, Alignment, Color};
 use iced::alignment::{Horizontal, Vertical};
-use iced::widget::{button, text, text_input, Column, TextInput, Text, Container, Row, Button, Tooltip, tooltip};
+use iced::widget::{text, text_input, pick_list, slider, checkbox, Column, TextInput, Text, Container, Row};
 use iced::window::close;
-use rand::{Rng};
-use crate::buttons::{check_button, finish_button, next_button, quit_button, restart_button, start_button};
-use crate::styles::{LineFakeButton};
-
-#[derive(Debug, Clone)]
+use clap::Parser;
+use propinva::difficulty::{Difficulty, MAX_BOUND};
+use propinva::engine::{format_duration, parse_answer, parse_input, unix_time, Mode, Session, SessionLength, Settings as SessionSettings};
+use propinva::history::SessionRecord;
+use propinva::problem::{relation_symbol, Kind, Mistake, Problem};
+use propinva::rational::Rational;
+use propinva::report::{file_name, Report};
+use propinva::worksheet::Worksheet;
+use crate::cli::{Cli, Command as CliCommand};
+use crate::buttons::{add_profile_button, back_button, check_button, delete_profile_button, export_button, finish_button, next_attempt_button, next_button, previous_attempt_button, quit_button, rename_profile_button, restart_button, retry_mistakes_button, review_button, start_button, yes_button, no_button, relation_button, hint_button};
+use crate::fractions::{graded_blanks, problem_view};
+use crate::store::Store;
+
+#[derive(Debug, Clone, Default)]
 enum Message {
+    #[default]
     Start,
-    ReadInput(String),
+    ReadInput(usize, String),
+    SubmitInput(usize),
     Check,
+    Choose(bool),
+    CycleRelation(usize),
+    ShowHint,
     Next,
     Finish,
     Restart,
+    SelectLength(LengthKind),
+    ReadLengthAmount(String),
+    SelectDifficulty(Difficulty),
+    SelectKind(Kind),
+    SetAdaptive(f32),
+    ToggleReviewDue(bool),
+    ToggleSigned(bool),
+    ToggleShuffleMistakes(bool),
+    ToggleChangeHiddenTerm(bool),
+    ReadRange(RangeField, String),
+    ReadSeed(String),
+    SelectProfile(String),
+    ReadProfileName(String),
+    AddProfile,
+    RenameProfile,
+    DeleteProfile,
+    Review,
+    ExportReport,
+    PreviousAttempt,
+    NextAttempt,
+    CloseReview,
+    RetryMistakes,
+    Tick,
     Quit
 }
 impl Display for Message {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Message::Start                  => {write!(f, "Start")},
-            Message::ReadInput(s)   => {write!(f, "{}", s)}
+            Message::ReadInput(blank, s)    => {write!(f, "{}: {}", blank, s)},
+            Message::SubmitInput(blank)     => {write!(f, "Submit {}", blank)},
             Message::Check                  => {write!(f, "Check")},
+            Message::Choose(yes)            => {write!(f, "{}", if *yes {"Yes"} else {"No"})},
+            Message::CycleRelation(gap)     => {write!(f, "Cycle relation {}", gap)},
+            Message::ShowHint               => {write!(f, "Show hint")},
             Message::Next                   => {write!(f, "Next")},
             Message::Finish                 => {write!(f, "Finish")},
             Message::Restart                => {write!(f, "Restart")},
+            Message::SelectLength(kind)     => {write!(f, "{}", kind)},
+            Message::ReadLengthAmount(s)    => {write!(f, "{}", s)},
+            Message::SelectDifficulty(d)    => {write!(f, "{}", d)},
+            Message::SelectKind(kind)       => {write!(f, "{}", kind)},
+            Message::SetAdaptive(strength)  => {write!(f, "Adaptive {}", strength)},
+            Message::ToggleReviewDue(on)    => {write!(f, "Review due {}", on)},
+            Message::ToggleSigned(on)       => {write!(f, "Signed {}", on)},
+            Message::ToggleShuffleMistakes(on)  => {write!(f, "Shuffle mistakes {}", on)},
+            Message::ToggleChangeHiddenTerm(on) => {write!(f, "Change hidden term {}", on)},
+            Message::ReadRange(field, s)    => {write!(f, "{:?}: {}", field, s)},
+            Message::ReadSeed(s)            => {write!(f, "Seed {}", s)},
+            Message::SelectProfile(name)    => {write!(f, "{}", name)},
+            Message::ReadProfileName(s)     => {write!(f, "{}", s)},
+            Message::AddProfile             => {write!(f, "Add profile")},
+            Message::RenameProfile          => {write!(f, "Rename profile")},
+            Message::DeleteProfile          => {write!(f, "Delete profile")},
+            Message::Review                 => {write!(f, "Review")},
+            Message::ExportReport           => {write!(f, "Export report")},
+            Message::PreviousAttempt        => {write!(f, "Previous attempt")},
+            Message::NextAttempt            => {write!(f, "Next attempt")},
+            Message::CloseReview            => {write!(f, "Close review")},
+            Message::RetryMistakes          => {write!(f, "Practice my mistakes")},
+            Message::Tick                   => {write!(f, "Tick")},
             Message::Quit                   => {write!(f, "Quit")},
         }
     }
 }
-impl Default for Message {
-    fn default() -> Self {
-        Message::Start
-    }
-}
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-enum Mode {
-    Start,
-    Exercising,
-    Result,
-    FinalEvaluation
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum LengthKind {
+    #[default]
+    Exercises,
+    Unlimited,
+    Minutes,
 }
-impl Display for Mode {
+impl LengthKind {
+    const ALL: [LengthKind; 3] = [LengthKind::Exercises, LengthKind::Unlimited, LengthKind::Minutes];
+}
+impl Display for LengthKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Mode::Start             => {write!(f, "Start")},
-            Mode::Exercising        => {write!(f, "Exercising")},
-            Mode::Result            => {write!(f, "Result")},
-            Mode::FinalEvaluation   => {write!(f, "FinalEvaluation")},
+            LengthKind::Exercises   => {write!(f, "Exercises")},
+            LengthKind::Unlimited   => {write!(f, "Unlimited")},
+            LengthKind::Minutes     => {write!(f, "Minutes")},
         }
     }
 }
-impl Default for Mode {
-    fn default() -> Self {
-        Mode::Start
-    }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RangeField {
+    MultiplierMin,
+    MultiplierMax,
+    NumberMin,
+    NumberMax,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, Default)]
 struct State {
-    mode: Mode,
-    error_made: bool,
-    errors_count: u32,
-    exercise_count: u32,
-    num_a: u32,
-    den_a: u32,
-    num_b: u32,
-    den_b: u32,
-    input_field_number: u32,
-    input_number: u32,
-    errors: [u32; 12],
-}
-impl Default for State {
-    fn default() -> Self {
-        State{
-            mode: Mode::default(),
-            error_made: false,
-            errors_count: 0,
-            exercise_count: 0,
-            num_a: 0,
-            den_a: 0,
-            num_b: 0,
-            den_b: 0,
-            input_field_number: 0,
-            input_number: 0,
-            errors: [0; 12],
-        }
-    }
-}
-impl Display for State {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "state: {}\nerror made: {}\terror count: {}\t exercise count: {}\nnum_a:  {}\tnum_b:  {}\nden_a:  {}\tden_b:  {}\ninput field: {}\tinput number: {}\n1: {}\t  4: {}\t  7: {}\t 10: {}\n2: {}\t  5: {}\t  8: {}\t 11: {}\n3: {}\t  6: {}\t  9: {}\t 12: {}\n",
-               self.mode, self.error_made, self.errors_count, self.exercise_count, self.num_a, self.num_b, self.den_a, self.den_b, self.input_field_number, self.input_number, self.errors[0], self.errors[3], self.errors[6], self.errors[9], self.errors[1], self.errors[4], self.errors[7], self.errors[10], self.errors[2], self.errors[5], self.errors[8], self.errors[11])
-    }
+    settings: SessionSettings,
+    session: Session,
+    /// What was typed in each blank of the problem on screen, and its value, zero when empty or invalid.
+    inputs: Vec<String>,
+    answers: Vec<Rational>,
+    length_kind: LengthKind,
+    length_amount: u32,
+    store: Store,
+    profile_name: String,
+    confirm_delete: bool,
+    /// Index of the attempt shown on the review screen, `None` outside of it.
+    review: Option<usize>,
+    /// Where the report of the finished session went, or why it could not be written.
+    exported: Option<String>,
 }
 
 impl State {
-    fn get_new_numbers (&mut self) {
-        let mul = rand::thread_rng().gen_range(2..11);
-        let num = rand::thread_rng().gen_range(1..13);
-        let den = rand::thread_rng().gen_range(1..13);
-
-        let a_or_b = rand::thread_rng().gen_bool(0.5);
-
-        self.num_a = if a_or_b {num} else {num*mul};
-        self.den_a = if a_or_b {den} else {den*mul};
-        self.num_b = if a_or_b {num*mul} else {num};
-        self.den_b = if a_or_b {den*mul} else {den};
-        self.input_field_number = rand::thread_rng().gen_range(0..4);
+    fn with_cli(cli: &Cli) -> Self {
+        let (store, settings) = Store::open(cli);
+        let mut state = State{store, ..State::default()};
+        state.load_settings(settings);
+        state
     }
-    fn start (&mut self) {
-        self.get_new_numbers();
-        self.input_number = 0;
-        self.mode = Mode::Exercising;
-    }
-    fn read_input(&mut self, input_string: String) {
-        self.input_number = match input_string.parse() {
-            Ok(number) => {if number>0 && number<1000 {number} else {0}}
-            Err(_) => {0}
+    fn load_settings(&mut self, settings: SessionSettings) {
+        (self.length_kind, self.length_amount) = match settings.length {
+            SessionLength::Count(count) => {(LengthKind::Exercises, count)}
+            SessionLength::Unlimited => {(LengthKind::Unlimited, 20)}
+            SessionLength::Timed(duration) => {(LengthKind::Minutes, (duration.as_secs()/60) as u32)}
         };
+        self.settings = settings;
+        self.session = Session::new(settings);
     }
-    fn evaluate (&mut self) {
-        self.exercise_count += 1;
-        match self.input_field_number {
-            0 => {
-                if self.input_number != self.num_a {
-                    self.errors_count += 1;
-                    self.error_made = true;
-                    let mul = if self.num_a > self.num_b {self.num_a/self.num_b} else {self.num_b/self.num_a};
-                    self.errors[mul as usize-1] += 1;
-                    let num = if self.num_a > self.num_b {self.num_b} else {self.num_a};
-                    self.errors[num as usize-1] += 1;
-                }
-            }
-            1 => {
-                if self.input_number != self.den_a {
-                    self.errors_count += 1;
-                    self.error_made = true;
-                    let mul = if self.den_a > self.den_b {self.den_a/self.den_b} else {self.den_b/self.den_a};
-                    self.errors[mul as usize-1] += 1;
-                    let num = if self.den_a > self.den_b {self.den_b} else {self.den_a};
-                    self.errors[num as usize-1] += 1;
-                }
-            }
-            2 => {
-                if self.input_number != self.num_b {
-                    self.errors_count += 1;
-                    self.error_made = true;
-                    let mul = if self.num_a > self.num_b {self.num_a/self.num_b} else {self.num_b/self.num_a};
-                    self.errors[mul as usize-1] += 1;
-                    let num = if self.num_a > self.num_b {self.num_b} else {self.num_a};
-                    self.errors[num as usize-1] += 1;
-                }
+    fn save_history(&mut self) {
+        // a failure is shown from `store.error`
+        let _ = self.store.save();
+    }
+    fn select_profile(&mut self, name: String) {
+        if let Some(profile) = self.store.history.profile(&name) {
+            self.load_settings(SessionSettings{seed: self.settings.seed, ..profile.settings});
+        }
+        self.store.history.selected = Some(name.clone());
+        self.store.profile = Some(name);
+        self.confirm_delete = false;
+        self.save_history();
+    }
+    fn read_profile_name(&mut self, input_string: String) {
+        self.profile_name = input_string;
+    }
+    fn add_profile(&mut self) {
+        match self.store.history.add_profile(&self.profile_name) {
+            Ok(profile) => {
+                profile.settings = SessionSettings{seed: None, ..self.settings};
+                let name = profile.name.clone();
+                self.profile_name.clear();
+                self.select_profile(name);
             }
-            3 => {
-                if self.input_number != self.den_b {
-                    self.errors_count += 1;
-                    self.error_made = true;
-                    let mul = if self.den_a > self.den_b {self.den_a/self.den_b} else {self.den_b/self.den_a};
-                    self.errors[mul as usize-1] += 1;
-                    let num = if self.den_a > self.den_b {self.den_b} else {self.den_a};
-                    self.errors[num as usize-1] += 1;
+            Err(error) => {self.store.error = Some(error.to_string())}
+        }
+    }
+    fn rename_profile(&mut self) {
+        if let Some(name) = self.store.profile.clone() {
+            match self.store.history.rename_profile(&name, &self.profile_name) {
+                Ok(()) => {
+                    self.store.profile = Some(String::from(self.profile_name.trim()));
+                    self.profile_name.clear();
+                    self.save_history();
                 }
+                Err(error) => {self.store.error = Some(error.to_string())}
             }
-            _ => {
-                panic!("Error: input field different from expected [0, 1, 2, 3]")
+        }
+    }
+    fn delete_profile(&mut self) {
+        if !self.confirm_delete {
+            self.confirm_delete = true;
+            return;
+        }
+        if let Some(name) = self.store.profile.take() {
+            match self.store.history.remove_profile(&name) {
+                Ok(_) => {self.save_history()}
+                Err(error) => {self.store.error = Some(error.to_string())}
             }
         }
-        self.mode = Mode::Result;
+        self.confirm_delete = false;
+    }
+    fn select_length(&mut self, length_kind: LengthKind) {
+        self.length_kind = length_kind;
+        self.update_length();
+    }
+    fn read_length_amount(&mut self, input_string: String) {
+        self.length_amount = parse_input(&input_string, 1000);
+        self.update_length();
+    }
+    /// An empty seed draws a new one for every session; a fixed one replays the same problems.
+    fn read_seed(&mut self, input_string: String) {
+        // 19 digits always fit in a u64
+        let digits: String = input_string.chars().filter(char::is_ascii_digit).take(19).collect();
+        self.settings.seed = digits.parse().ok();
+    }
+    fn update_length(&mut self) {
+        self.settings.length = match self.length_kind {
+            LengthKind::Exercises => {SessionLength::Count(self.length_amount.max(1))}
+            LengthKind::Unlimited => {SessionLength::Unlimited}
+            LengthKind::Minutes => {SessionLength::Timed(Duration::from_secs(self.length_amount.max(1) as u64 * 60))}
+        };
+    }
+    fn select_difficulty(&mut self, difficulty: Difficulty) {
+        self.settings.difficulty = difficulty;
+    }
+    fn select_kind(&mut self, kind: Kind) {
+        self.settings.kind = kind;
+    }
+    fn toggle_review_due(&mut self, review_due: bool) {
+        self.settings.review_due = review_due;
+    }
+    fn toggle_signed(&mut self, signed: bool) {
+        self.settings.signed = signed;
+    }
+    fn toggle_shuffle_mistakes(&mut self, shuffle: bool) {
+        self.settings.retry.shuffle = shuffle;
+    }
+    fn toggle_change_hidden_term(&mut self, change_hidden_term: bool) {
+        self.settings.retry.change_hidden_term = change_hidden_term;
+    }
+    fn set_adaptive(&mut self, strength: f32) {
+        self.settings.adaptive = strength;
+    }
+    fn read_range(&mut self, field: RangeField, input_string: String) {
+        let value = parse_input(&input_string, MAX_BOUND + 1);
+        let ranges = &mut self.settings.custom_ranges;
+        match field {
+            RangeField::MultiplierMin => {ranges.multiplier.min = value}
+            RangeField::MultiplierMax => {ranges.multiplier.max = value}
+            RangeField::NumberMin => {ranges.number.min = value}
+            RangeField::NumberMax => {ranges.number.max = value}
+        }
+    }
+    fn start (&mut self) {
+        self.session = self.store.session(self.settings);
+        self.session.start();
+        self.clear_answers();
+        self.confirm_delete = false;
+        self.review = None;
+    }
+    fn clear_answers(&mut self) {
+        let blanks = self.session.problem().blanks();
+        self.inputs = vec![String::new(); blanks];
+        self.answers = vec![Rational::ZERO; blanks];
+    }
+    /// Keeps digits, minus signs, decimal points, percent signs and slashes only; `parse_answer` decides
+    /// which of them the blank takes, like in the terminal.
+    fn read_input(&mut self, blank: usize, input_string: String) {
+        let input_string: String = input_string.chars().filter(|c| c.is_ascii_digit() || "-.,%/".contains(*c)).collect();
+        if let Some(answer) = self.answers.get_mut(blank) {
+            *answer = parse_answer(&self.session.problem(), blank, &input_string, self.session.input_limit());
+        }
+        if let Some(input) = self.inputs.get_mut(blank) {
+            *input = input_string;
+        }
+    }
+    fn is_answered(&self) -> bool {
+        !self.answers.iter().any(Rational::is_zero)
+    }
+    /// Enter in a blank moves to the next one, and checks the answers from the last one.
+    fn submit_input(&mut self, blank: usize) -> Command<Message> {
+        if blank+1 < self.answers.len() {
+            text_input::focus(blank_id(blank+1))
+        } else if self.is_answered() {
+            self.evaluate();
+            Command::none()
+        } else {
+            Command::none()
+        }
+    }
+    fn evaluate (&mut self) {
+        self.session.answer(self.answers.clone());
+    }
+    fn choose (&mut self, yes: bool) {
+        self.session.answer(vec![Rational::from(yes as u32)]);
+    }
+    /// Each press moves the sign between two compared fractions to the next of `<`, `=` and `>`.
+    fn cycle_relation (&mut self, gap: usize) {
+        if let Some(answer) = self.answers.get_mut(gap) {
+            *answer = Rational::from(answer.to_u32().unwrap_or(0) % 3 + 1);
+        }
+    }
+    fn show_hint (&mut self) {
+        self.session.use_hint();
     }
     fn next (&mut self) {
-        self.get_new_numbers();
-        self.input_number = 0;
-        self.error_made = false;
-        self.mode = Mode::Exercising;
+        self.session.next();
+        self.clear_answers();
     }
     fn finish (&mut self) {
-        self.mode = Mode::FinalEvaluation;
-    }
-    fn restart (&mut self) {
-        self.mode = Mode::Exercising;
-        self.error_made = false;
-        self.errors_count= 0;
-        self.exercise_count= 0;
-        self.num_a= 0;
-        self.den_a= 0;
-        self.num_b= 0;
-        self.den_b= 0;
-        self.input_field_number= 0;
-        self.input_number= 0;
-        self.errors = [0; 12];
-        self.get_new_numbers();
+        self.session.finish();
+        // a failure is shown from `store.error`
+        let _ = self.store.record(&self.session);
+        self.exported = None;
+    }
+    fn retry_mistakes (&mut self) {
+        let Some(session) = self.session.retry_mistakes() else {
+            return;
+        };
+        self.session = session;
+        self.session.start();
+        self.clear_answers();
+        self.review = None;
+    }
+    fn review (&mut self) {
+        self.review = if self.session.attempts().is_empty() {None} else {Some(0)};
+    }
+    /// Writes the report of the session as CSV and JSON in the `reports` folder next to the history.
+    fn export_report (&mut self) {
+        let name = self.store.name();
+        let folder = self.store.path.as_deref().and_then(Path::parent).map(Path::to_path_buf).unwrap_or_default().join("reports");
+        let file = format!("{}-{}", file_name(&name), self.session.timestamp());
+        let report = Report::new(&name, [&SessionRecord::from(&self.session)]);
+        let written = report.export(&folder.join(format!("{}.csv", file))).and_then(|_| report.export(&folder.join(format!("{}.json", file))));
+        self.exported = Some(match written {
+            Ok(_) => {format!("Report saved in {}", folder.display())}
+            Err(error) => {format!("Report not saved: {}", error)}
+        });
+    }
+    fn previous_attempt (&mut self) {
+        self.review = self.review.map(|index| index.saturating_sub(1));
+    }
+    fn next_attempt (&mut self) {
+        let last = self.session.attempts().len().saturating_sub(1);
+        self.review = self.review.map(|index| (index+1).min(last));
+    }
+    fn close_review (&mut self) {
+        self.review = None;
     }
 }
 
+fn blank_id(blank: usize) -> text_input::Id {
+    text_input::Id::new(format!("blank-{}", blank))
+}
+
 impl Application for State{
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = Cli;
 
-    fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        (Self::default(), Command::none())
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        (State::with_cli(&flags), Command::none())
     }
     fn title(&self) -> String {
         String::from("PropInva")
@@ -219,22 +393,37 @@ impl Application for State{
             Message::Start                  => {
                 self.start();
                 //println!("Start");
-                Command::none()
+                text_input::focus(blank_id(0))
             }
-            Message::ReadInput(input_string)    => {
-                self.read_input(input_string);
-                //println!("{}",self.input_number);
+            Message::ReadInput(blank, input_string)    => {
+                self.read_input(blank, input_string);
+                //println!("{:?}",self.answers);
                 Command::none()
             }
+            Message::SubmitInput(blank)     => {
+                self.submit_input(blank)
+            }
             Message::Check                  => {
                 self.evaluate();
                 //println!("Check");
                 Command::none()
             }
+            Message::Choose(yes)            => {
+                self.choose(yes);
+                Command::none()
+            }
+            Message::CycleRelation(gap)     => {
+                self.cycle_relation(gap);
+                Command::none()
+            }
+            Message::ShowHint               => {
+                self.show_hint();
+                Command::none()
+            }
             Message::Next                   => {
                 self.next();
                 //println!("Next");
-                Command::none()
+                text_input::focus(blank_id(0))
             }
             Message::Finish                 => {
                 self.finish();
@@ -242,8 +431,104 @@ impl Application for State{
                 Command::none()
             }
             Message::Restart                =>  {
-                self.restart();
+                // through `start` so the facts that fell due and this session's weaknesses are loaded again
+                self.start();
                 //println!("Restart");
+                text_input::focus(blank_id(0))
+            }
+            Message::SelectLength(length_kind)  => {
+                self.select_length(length_kind);
+                Command::none()
+            }
+            Message::ReadLengthAmount(input_string) => {
+                self.read_length_amount(input_string);
+                Command::none()
+            }
+            Message::ReadSeed(input_string) => {
+                self.read_seed(input_string);
+                Command::none()
+            }
+            Message::SelectDifficulty(difficulty)  => {
+                self.select_difficulty(difficulty);
+                Command::none()
+            }
+            Message::SelectKind(kind)       => {
+                self.select_kind(kind);
+                Command::none()
+            }
+            Message::SetAdaptive(strength)  => {
+                self.set_adaptive(strength);
+                Command::none()
+            }
+            Message::ToggleReviewDue(review_due) => {
+                self.toggle_review_due(review_due);
+                Command::none()
+            }
+            Message::ToggleSigned(signed)   => {
+                self.toggle_signed(signed);
+                Command::none()
+            }
+            Message::ToggleShuffleMistakes(shuffle) => {
+                self.toggle_shuffle_mistakes(shuffle);
+                Command::none()
+            }
+            Message::ToggleChangeHiddenTerm(change_hidden_term) => {
+                self.toggle_change_hidden_term(change_hidden_term);
+                Command::none()
+            }
+            Message::ReadRange(field, input_string) => {
+                self.read_range(field, input_string);
+                Command::none()
+            }
+            Message::SelectProfile(name)    => {
+                self.select_profile(name);
+                Command::none()
+            }
+            Message::ReadProfileName(input_string) => {
+                self.read_profile_name(input_string);
+                Command::none()
+            }
+            Message::AddProfile             => {
+                self.add_profile();
+                Command::none()
+            }
+            Message::RenameProfile          => {
+                self.rename_profile();
+                Command::none()
+            }
+            Message::DeleteProfile          => {
+                self.delete_profile();
+                Command::none()
+            }
+            Message::Review                 => {
+                self.review();
+                Command::none()
+            }
+            Message::ExportReport           => {
+                self.export_report();
+                Command::none()
+            }
+            Message::PreviousAttempt        => {
+                self.previous_attempt();
+                Command::none()
+            }
+            Message::NextAttempt            => {
+                self.next_attempt();
+                Command::none()
+            }
+            Message::CloseReview            => {
+                self.close_review();
+                Command::none()
+            }
+            Message::RetryMistakes          => {
+                self.retry_mistakes();
+                text_input::focus(blank_id(0))
+            }
+            Message::Tick                   => {
+                // the problem left unanswered when the time is up is not graded
+                if self.session.mode() == Mode::Exercising && self.session.remaining_time() == Some(Duration::ZERO) {
+                    self.finish();
+                }
                 Command::none()
             }
             Message::Quit                   => {
@@ -257,7 +542,15 @@ impl Application for State{
         let start_button = start_button();
 
         let check_button = check_button();
-        let check_button = if self.input_number != 0 {check_button.on_press(Message::Check)} else {check_button};
+        let check_button = if self.is_answered() {check_button.on_press(Message::Check)} else {check_button};
+        let answer_buttons = match self.session.problem() {
+            Problem::Equivalent(_) => {Row::new().push(no_button()).push(yes_button()).spacing(10)}
+            Problem::Compare(_) => {
+                let hint_button = if self.session.hinted() {hint_button()} else {hint_button().on_press(Message::ShowHint)};
+                Row::new().push(hint_button).push(check_button).spacing(10)
+            }
+            _ => {Row::new().push(check_button)}
+        };
 
         let next_button = next_button();
 
@@ -267,439 +560,301 @@ impl Application for State{
 
         let quit_button = quit_button();
 
-        let start_title: Text<'_, Theme, Renderer> = text("Exercises on Invariant Property").size(35);
-        let result_labels: Text<'_, Theme, Renderer> = text("Correct:\nErrors:\nAccuracy:").size(41);
-        let result_numbers: Text<'_, Theme, Renderer> = text(format!("{}\n{}\n{}%",
-                                                                    self.exercise_count-self.errors_count,
-                                                                    self.errors_count,
-                                                                    (self.exercise_count as f32 - self.errors_count as f32) / self.exercise_count as f32 * 100.0)).size(41);
-
-        let max_err = self.errors.iter().max().unwrap();
-        let repeated_err = *max_err >= 2;
+        let review_button = review_button();
+        let review_button = if !self.session.attempts().is_empty() {review_button.on_press(Message::Review)} else {review_button};
 
-        let focus_text: Text<'_, Theme, Renderer> = text(format!("You should focus on the {} times table", max_err)).size(21);
+        let export_button = export_button();
+        let export_button = if !self.session.attempts().is_empty() {export_button.on_press(Message::ExportReport)} else {export_button};
 
-        let equal_sign = text("=").size(50);
-        let inserted = text(format!("Inserted: {}", self.input_number.to_string())).size(30).style(Color::from_rgb(0.6, 0.8, 1.0));
+        let retry_mistakes_button = retry_mistakes_button();
+        let retry_mistakes_button = if !self.session.mistakes().is_empty() {retry_mistakes_button.on_press(Message::RetryMistakes)} else {retry_mistakes_button};
 
-        let numerator_a =
-            if self.mode == Mode::Result && self.input_field_number == 0 {
-                if self.error_made {
-                    text(self.num_a.to_string()).size(46).style(Color::from_rgb(0.9, 0.35, 0.26))
-                } else {
-                    text(self.num_a.to_string()).size(46).style(Color::from_rgb(0.4, 0.8, 0.0))
-                }
-            }
-            else {
-                text(self.num_a.to_string()).size(46)
-            };
+        let problem = self.session.problem();
+        let summary = self.session.summary();
+        let mode = self.session.mode();
 
-        let denominator_a =
-            if self.mode == Mode::Result && self.input_field_number == 1 {
-                if self.error_made {
-                    text(self.den_a.to_string()).size(46).style(Color::from_rgb(0.9, 0.35, 0.26))
-                } else {
-                    text(self.den_a.to_string()).size(46).style(Color::from_rgb(0.4, 0.8, 0.0))
-                }
-            }
-            else {
-                text(self.den_a.to_string()).size(46)
-            };
+        let result_buttons = if self.session.is_over() {
+            Row::new().push(finish_button)
+        } else if matches!(self.session.settings().length, SessionLength::Count(_)) && !self.session.is_retry() {
+            Row::new().push(next_button)
+        } else {
+            Row::new().push(finish_button).push(next_button).spacing(10)
+        };
 
-        let numerator_b =
-            if self.mode == Mode::Result && self.input_field_number == 2 {
-                if self.error_made {
-                    text(self.num_b.to_string()).size(46).style(Color::from_rgb(0.9, 0.35, 0.26))
-                }
-                else {
-                    text(self.num_b.to_string()).size(46).style(Color::from_rgb(0.4, 0.8, 0.0))
-                }
-            }
-            else {
-                text(self.num_b.to_string()).size(46)
-            };
+        let progress = match (self.session.settings().length, self.session.remaining_time()) {
+            _ if self.session.is_retry() => {format!("{} to fix", self.session.remaining_mistakes().unwrap_or(0))}
+            (_, Some(remaining_time)) => {format!("{} left", format_duration(remaining_time))}
+            (SessionLength::Count(count), _) => {format!("{}/{}", self.session.exercise_count(), count)}
+            _ => {format!("{}", self.session.exercise_count())}
+        };
+        let status_text: Text<'_, Theme, Renderer> = text(match self.session.due_count() {
+            0 => {progress}
+            due_count => {format!("{}   {} to review", progress, due_count)}
+        }).size(24);
+
+        let length_picker = pick_list(LengthKind::ALL, Some(self.length_kind), Message::SelectLength)
+            .text_size(22)
+            .width(Length::Fixed(160.0));
+        let length_amount = if self.length_amount==0 {String::new()} else {self.length_amount.to_string()};
+        let length_input: TextInput<'_, Message, Theme, Renderer> = text_input("", &length_amount)
+            .size(22)
+            .width(Length::Fixed(70.0));
+        let length_input = if self.length_kind != LengthKind::Unlimited {length_input.on_input(Message::ReadLengthAmount)} else {length_input};
+
+        let kind_picker = pick_list(Kind::ALL, Some(self.settings.kind), Message::SelectKind)
+            .text_size(22)
+            .width(Length::Fixed(190.0));
+        let difficulty_picker = pick_list(Difficulty::ALL, Some(self.settings.difficulty), Message::SelectDifficulty)
+            .text_size(22)
+            .width(Length::Fixed(160.0));
+        let due_count = self.store.history.profile(&self.store.name())
+            .map(|profile| profile.schedule.due_count(unix_time()))
+            .unwrap_or(0);
+        let review_due_checkbox = checkbox(format!("Review {} due facts first", due_count), self.settings.review_due)
+            .on_toggle(Message::ToggleReviewDue)
+            .text_size(18)
+            .size(18);
+        let signed_checkbox = checkbox("Negative numbers", self.settings.signed)
+            .on_toggle(Message::ToggleSigned)
+            .text_size(18)
+            .size(18);
+        let seed_input: TextInput<'_, Message, Theme, Renderer> = text_input("random", &self.settings.seed.map(|seed| seed.to_string()).unwrap_or_default())
+            .size(18)
+            .width(Length::Fixed(110.0))
+            .on_input(Message::ReadSeed);
+        let shuffle_mistakes_checkbox = checkbox("Practise mistakes in random order", self.settings.retry.shuffle)
+            .on_toggle(Message::ToggleShuffleMistakes)
+            .text_size(18)
+            .size(18);
+        let change_hidden_term_checkbox = checkbox("Hide another term of the mistakes", self.settings.retry.change_hidden_term)
+            .on_toggle(Message::ToggleChangeHiddenTerm)
+            .text_size(18)
+            .size(18);
+        let adaptive_slider = slider(0.0..=1.0, self.settings.adaptive, Message::SetAdaptive)
+            .step(0.25)
+            .width(Length::Fixed(120.0));
+        let adaptive_text: Text<'_, Theme, Renderer> = text(match self.settings.adaptive {
+            strength if strength <= 0.0 => {String::from("Adaptive: off")}
+            strength => {format!("Adaptive: {}%", (strength*100.0).round())}
+        }).size(18);
+        let range_input = |field: RangeField, value: u32| -> TextInput<'_, Message, Theme, Renderer> {
+            text_input("", &if value==0 {String::new()} else {value.to_string()})
+                .size(18)
+                .width(Length::Fixed(50.0))
+                .on_input(move |input_string| Message::ReadRange(field, input_string))
+        };
+        let custom_ranges = self.settings.custom_ranges;
+        let range_editor = Row::new()
+            .push(text("Multipliers").size(18))
+            .push(range_input(RangeField::MultiplierMin, custom_ranges.multiplier.min))
+            .push(text("-").size(18))
+            .push(range_input(RangeField::MultiplierMax, custom_ranges.multiplier.max))
+            .push(Container::new(text("Numbers").size(18)).padding(Padding{top: 0.0, right: 0.0, bottom: 0.0, left: 20.0}))
+            .push(range_input(RangeField::NumberMin, custom_ranges.number.min))
+            .push(text("-").size(18))
+            .push(range_input(RangeField::NumberMax, custom_ranges.number.max))
+            .spacing(6)
+            .align_items(Alignment::Center);
+
+        let profile_picker = pick_list(self.store.history.names(), self.store.profile.clone(), Message::SelectProfile)
+            .placeholder("Student")
+            .text_size(18)
+            .width(Length::Fixed(150.0));
+        let profile_name_input: TextInput<'_, Message, Theme, Renderer> = text_input("Name", &self.profile_name)
+            .size(18)
+            .width(Length::Fixed(130.0))
+            .on_input(Message::ReadProfileName)
+            .on_submit(Message::AddProfile);
+        let has_name = !self.profile_name.trim().is_empty();
+        let add_profile_button = add_profile_button();
+        let add_profile_button = if has_name {add_profile_button.on_press(Message::AddProfile)} else {add_profile_button};
+        let rename_profile_button = rename_profile_button();
+        let rename_profile_button = if has_name && self.store.profile.is_some() {rename_profile_button.on_press(Message::RenameProfile)} else {rename_profile_button};
+        let delete_profile_button = delete_profile_button(self.confirm_delete);
+        let delete_profile_button = if self.store.profile.is_some() {delete_profile_button.on_press(Message::DeleteProfile)} else {delete_profile_button};
+        let notice_text: Text<'_, Theme, Renderer> = text(self.store.error.clone().unwrap_or_default()).size(14).style(Color::from_rgb(0.9, 0.35, 0.26));
 
-        let denominator_b =
-            if self.mode == Mode::Result && self.input_field_number == 3 {
-                if self.error_made {
-                    text(self.den_b.to_string()).size(46).style(Color::from_rgb(0.9, 0.35, 0.26))
-                } else {
-                    text(self.den_b.to_string()).size(46).style(Color::from_rgb(0.4, 0.8, 0.0))
+        let start_title: Text<'_, Theme, Renderer> = text("Exercises on Invariant Property").size(35);
+        let headline: Text<'_, Theme, Renderer> = text(match &self.store.profile {
+            Some(name) => {format!("{}: {}", name, summary.headline())}
+            None => {summary.headline()}
+        }).size(24);
+        let result_labels: Text<'_, Theme, Renderer> = text("Correct:\nErrors:\nAccuracy:").size(41);
+        let result_numbers: Text<'_, Theme, Renderer> = text(format!("{}\n{}\n{}%",
+                                                                    summary.correct,
+                                                                    summary.errors,
+                                                                    summary.accuracy)).size(41);
+
+        let advice = self.session.weakness().advice();
+        let advice = match self.session.weakness().breakdown() {
+            Some(breakdown) => {[vec![breakdown], advice].concat()}
+            None => {advice}
+        };
+        let repeated_err = !advice.is_empty();
+
+        let focus_text: Text<'_, Theme, Renderer> = text(advice.join("\n")).size(21);
+        let seed_text: Text<'_, Theme, Renderer> = text(match (&self.store.error, &self.exported) {
+            (Some(error), _) => {format!("Seed: {}    Not saved: {}", self.session.seed(), error)}
+            (None, Some(exported)) => {format!("Seed: {}    {}", self.session.seed(), exported)}
+            (None, None) => {format!("Seed: {}", self.session.seed())}
+        }).size(16);
+
+        // four fractions of a chain must fit side by side
+        let input_width = if let Problem::Chain(_) | Problem::Decimal(_) = problem {80.0} else {100.0};
+        let input_fields = self.answers.iter().zip(&self.inputs).enumerate()
+            .map(|(blank, (answer, input))| -> Element<'_, Message, Theme, Renderer> {
+                if let Problem::Compare(_) = problem {
+                    return relation_button(blank, relation_symbol(answer.to_u32().unwrap_or(0))).into();
                 }
-            }
-            else {
-                text(self.den_b.to_string()).size(46)
+                let input_field : TextInput<'_, Message, Theme, Renderer> = text_input("", input)
+                    .id(blank_id(blank))
+                    .size(38.4)
+                    .width(Length::Fixed(input_width))
+                    .on_input(move |input_string| Message::ReadInput(blank, input_string))
+                    .on_paste(move |input_string| Message::ReadInput(blank, input_string))
+                    .on_submit(Message::SubmitInput(blank));
+                input_field.into()
+            })
+            .collect();
+        // yes or no questions are answered with buttons only
+        let input_fields = if let Problem::Equivalent(_) = problem {Vec::new()} else {input_fields};
+
+        if let Some(index) = self.review {
+            let attempt = &self.session.attempts()[index];
+            let previous_button = previous_attempt_button();
+            let previous_button = if index > 0 {previous_button.on_press(Message::PreviousAttempt)} else {previous_button};
+            let next_button = next_attempt_button();
+            let next_button = if index+1 < self.session.attempts().len() {next_button.on_press(Message::NextAttempt)} else {next_button};
+            let verdict_text: Text<'_, Theme, Renderer> = if attempt.correct {
+                text("Correct").size(21).style(Color::from_rgb(0.4, 0.8, 0.0))
+            } else {
+                let problem = attempt.problem;
+                let verdict = format!("Inserted {}, the answer is {}", problem.format_answers(&attempt.answers), problem.format_answers(&problem.solution()));
+                text(match problem.grade(&attempt.answers) {
+                    Err(mistake) if mistake != Mistake::WrongTerm => {format!("{} ({})", verdict, mistake)}
+                    _ => {verdict}
+                }).size(21).style(Color::from_rgb(0.9, 0.35, 0.26))
             };
+            return Column::new()
+                .push(Container::new(Column::new()
+                        .push(text(format!("Problem {}/{}", index+1, self.session.attempts().len())).size(24))
+                        .push(verdict_text)
+                        .align_items(Alignment::Center)
+                    ).padding(Padding{top: 20.0, right: 0.0, bottom: 0.0, left: 0.0})
+                )
+                .push(problem_view(&attempt.problem, graded_blanks(&attempt.problem, &attempt.answers), attempt.hinted))
+                .push(Row::new()
+                    .push(Container::new(back_button())
+                        .width(Length::Fill)
+                        .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 50.0})
+                    )
+                    .push(Container::new(Row::new()
+                            .push(previous_button)
+                            .push(next_button)
+                            .spacing(10)
+                        ).padding(Padding{top: 0.0, right: 50.0, bottom: 30.0, left: 0.0})
+                    )
+                    .align_items(Alignment::Center)
+                )
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_items(Alignment::Center)
+                .into();
+        }
 
-        let line_a: Button<'_, Message, Theme, Renderer> = button("")
-            .style(LineFakeButton::new())
-            .width(Length::Fixed(120.0))
-            .height(Length::Fixed(2.0));
-        let line_b: Button<'_, Message, Theme, Renderer> = button("")
-            .style(LineFakeButton::new())
-            .width(Length::Fixed(100.0))
-            .height(Length::Fixed(2.0));
-
-        let displayed_value = if self.input_number==0 {""} else {&self.input_number.to_string()};
-        let input_field : TextInput<'_, Message, Theme, Renderer> = text_input("", displayed_value)
-            .size(38.4)
-            .width(Length::Fixed(100.0))
-            .on_input(Message::ReadInput)
-            .on_paste(Message::ReadInput)
-            .on_submit(Message::Check);
-
-        match self.mode{
+        match mode{
             Mode::Start => {
                 Column::new()
                     .width(Length::Fill)
                     .align_items(Alignment::Center)
-                    .push(Container::new(start_title).padding(Padding{top: 60.0, right: 0.0, bottom: 0.0, left: 0.0}))
-                    .push(Container::new(start_button).padding(Padding{top: 50.0, right: 0.0, bottom: 0.0, left: 0.0}))
-                    .push(Container::new(quit_button).padding(Padding{top: 50.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(Container::new(start_title).padding(Padding{top: 20.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(Container::new(Row::new()
+                            .push(profile_picker)
+                            .push(profile_name_input)
+                            .push(add_profile_button)
+                            .push(rename_profile_button)
+                            .push(delete_profile_button)
+                            .spacing(8)
+                            .align_items(Alignment::Center)
+                        ).padding(Padding{top: 20.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(notice_text)
+                    .push(Container::new(Row::new()
+                            .push(kind_picker)
+                            .push(signed_checkbox)
+                            .push(text("Seed").size(18))
+                            .push(seed_input)
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                        ).padding(Padding{top: 10.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(Container::new(Row::new()
+                            .push(length_picker)
+                            .push(length_input)
+                            .push(review_due_checkbox)
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                        ).padding(Padding{top: 10.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(Container::new(Row::new()
+                            .push(shuffle_mistakes_checkbox)
+                            .push(change_hidden_term_checkbox)
+                            .spacing(20)
+                            .align_items(Alignment::Center)
+                        ).padding(Padding{top: 10.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(Container::new(Row::new()
+                            .push(difficulty_picker)
+                            .push(adaptive_slider)
+                            .push(adaptive_text)
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                        ).padding(Padding{top: 10.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                    .push(if self.settings.difficulty == Difficulty::Custom {
+                            Container::new(range_editor).padding(Padding{top: 10.0, right: 0.0, bottom: 0.0, left: 0.0})
+                        } else {
+                            Container::new(text(format!("Multipliers {}, numbers {}", self.settings.ranges().multiplier, self.settings.ranges().number)).size(18))
+                                .padding(Padding{top: 10.0, right: 0.0, bottom: 0.0, left: 0.0})
+                        })
+                    .push(Container::new(Row::new()
+                            .push(quit_button)
+                            .push(start_button)
+                            .spacing(60)
+                            .align_items(Alignment::Center)
+                        ).padding(Padding{top: 20.0, right: 0.0, bottom: 0.0, left: 0.0}))
                     .into()
             }
             Mode::Exercising => {
-                match self.input_field_number {
-                    0 => {
-                        Column::new()
-                        .push(Container::new(Row::new()
-                            .push(Container::new(Column::new()
-                                    .push(input_field)
-                                    .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(denominator_a)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Right)
-                            )
-                            .push(Container::new(equal_sign)
-                                .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                .height(Length::Fill)
-                                .center_x()
-                                .center_y()
-                            )
-                            .push(Container::new(Column::new()
-                                    .push(numerator_b)
-                                    .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(denominator_b)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Left)
-                            ))
-                            .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_x(Horizontal::Center)
-                            .align_y(Vertical::Center)
-                        )
-                        .push(Container::new(check_button)
-                            .width(Length::Fill)
-                            .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
-                        )
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .align_items(Alignment::Center)
-                        .into()
-                    }
-                    1 => {
-                        Column::new()
-                        .push(Container::new(Row::new()
-                            .push(Container::new(Column::new()
-                                    .push(numerator_a)
-                                    .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(input_field)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Right)
-                            )
-                            .push(Container::new(equal_sign)
-                                .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                .height(Length::Fill)
-                                .center_x()
-                                .center_y()
-                            )
-                            .push(Container::new(Column::new()
-                                    .push(numerator_b)
-                                    .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(denominator_b)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Left)
-                            ))
-                            .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_x(Horizontal::Center)
-                            .align_y(Vertical::Center)
-                        )
-                        .push(Container::new(check_button)
-                            .width(Length::Fill)
-                            .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
-                        )
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .align_items(Alignment::Center)
-                        .into()
-                    }
-                    2 => {
-                        Column::new()
-                        .push(Container::new(Row::new()
-                            .push(Container::new(Column::new()
-                                    .push(numerator_a)
-                                    .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(denominator_a)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Right)
-                            )
-                            .push(Container::new(equal_sign)
-                                .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                .height(Length::Fill)
-                                .center_x()
-                                .center_y()
-                            )
-                            .push(Container::new(Column::new()
-                                    .push(input_field)
-                                    .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(denominator_b)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Left)
-                            ))
+                Column::new()
+                    .push(problem_view(&problem, input_fields, self.session.hinted()))
+                    .push(Row::new()
+                        .push(Container::new(status_text)
                             .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_x(Horizontal::Center)
-                            .align_y(Vertical::Center)
+                            .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 50.0})
                         )
-                        .push(Container::new(check_button)
-                            .width(Length::Fill)
-                            .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
+                        .push(Container::new(answer_buttons)
+                            .padding(Padding{top: 0.0, right: 50.0, bottom: 30.0, left: 0.0})
                         )
-                        .width(Length::Fill)
-                        .height(Length::Fill)
                         .align_items(Alignment::Center)
-                        .into()
-                    }
-                    3 => {
-                        Column::new()
-                        .push(Container::new(Row::new()
-                            .push(Container::new(Column::new()
-                                    .push(numerator_a)
-                                    .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(denominator_a)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Right)
-                            )
-                            .push(Container::new(equal_sign)
-                                .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                .height(Length::Fill)
-                                .center_x()
-                                .center_y()
-                            )
-                            .push(Container::new(Column::new()
-                                    .push(numerator_b)
-                                    .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                    .push(input_field)
-                                    .align_items(Alignment::Center)
-                                ).height(Length::Fill)
-                                .align_y(Vertical::Center)
-                                .align_x(Horizontal::Left)
-                            ))
+                    )
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_items(Alignment::Center)
+                    .into()
+            }
+            Mode::Result => {
+                Column::new()
+                    .push(problem_view(&problem, graded_blanks(&problem, self.session.answers()), self.session.hinted()))
+                    .push(Row::new()
+                        .push(Container::new(status_text)
                             .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_x(Horizontal::Center)
-                            .align_y(Vertical::Center)
+                            .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 50.0})
                         )
-                        .push(Container::new(check_button)
-                            .width(Length::Fill)
-                            .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
+                        .push(Container::new(result_buttons)
+                            .padding(Padding{top: 0.0, right: 50.0, bottom: 30.0, left: 0.0})
                         )
-                        .width(Length::Fill)
-                        .height(Length::Fill)
                         .align_items(Alignment::Center)
-                        .into()
-                    }
-                    _ => {
-                        panic!("Error: input field different from expected [0, 1, 2, 3]");
-                        // Column::new().push(text("Error: input field different from expected [0, 1, 2, 3]").size(35)).into()
-                    }
-                }
-            }
-            Mode::Result => {
-                match self.input_field_number {
-                    0 => {
-                        Column::new()
-                            .push(Container::new(Row::new()
-                                .push(Container::new(Column::new()
-                                        .push(Tooltip::new(numerator_a, inserted, tooltip::Position::Top))
-                                        .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(denominator_a)
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Right)
-                                )
-                                .push(Container::new(equal_sign)
-                                    .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                    .height(Length::Fill)
-                                    .center_x()
-                                    .center_y()
-                                )
-                                .push(Container::new(Column::new()
-                                        .push(numerator_b)
-                                        .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(denominator_b)
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Left)
-                                ))
-                                .width(Length::Fill)
-                                .height(Length::Fill)
-                                .align_x(Horizontal::Center)
-                                .align_y(Vertical::Center)
-                            )
-                            .push(Container::new(if self.exercise_count <20 {next_button} else {finish_button})
-                                .width(Length::Fill)
-                                .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
-                            )
-                            .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_items(Alignment::Center)
-                            .into()
-                    }
-                    1 => {
-                        Column::new()
-                            .push(Container::new(Row::new()
-                                .push(Container::new(Column::new()
-                                        .push(numerator_a)
-                                        .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(Tooltip::new(denominator_a, inserted, tooltip::Position::Bottom))
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Right)
-                                )
-                                .push(Container::new(equal_sign)
-                                    .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                    .height(Length::Fill)
-                                    .center_x()
-                                    .center_y()
-                                )
-                                .push(Container::new(Column::new()
-                                        .push(numerator_b)
-                                        .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(denominator_b)
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Left)
-                                ))
-                                .width(Length::Fill)
-                                .height(Length::Fill)
-                                .align_x(Horizontal::Center)
-                                .align_y(Vertical::Center)
-                            )
-                            .push(Container::new(if self.exercise_count <20 {next_button} else {finish_button})
-                                .width(Length::Fill)
-                                .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
-                            )
-                            .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_items(Alignment::Center)
-                            .into()
-                    }
-                    2 => {
-                        Column::new()
-                            .push(Container::new(Row::new()
-                                .push(Container::new(Column::new()
-                                        .push(numerator_a)
-                                        .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(denominator_a)
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Right)
-                                )
-                                .push(Container::new(equal_sign)
-                                    .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                    .height(Length::Fill)
-                                    .center_x()
-                                    .center_y()
-                                )
-                                .push(Container::new(Column::new()
-                                        .push(Tooltip::new(numerator_b, inserted, tooltip::Position::Top))
-                                        .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(denominator_b)
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Left)
-                                ))
-                                .width(Length::Fill)
-                                .height(Length::Fill)
-                                .align_x(Horizontal::Center)
-                                .align_y(Vertical::Center)
-                            )
-                            .push(Container::new(if self.exercise_count <20 {next_button} else {finish_button})
-                                .width(Length::Fill)
-                                .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
-                            )
-                            .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_items(Alignment::Center)
-                            .into()
-                    }
-                    3 => {
-                        Column::new()
-                            .push(Container::new(Row::new()
-                                .push(Container::new(Column::new()
-                                        .push(numerator_a)
-                                        .push(Container::new(line_a).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(denominator_a)
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Right)
-                                )
-                                .push(Container::new(equal_sign)
-                                    .padding(Padding{top: 0.0, right: 50.0, bottom: 00.0, left: 50.0})
-                                    .height(Length::Fill)
-                                    .center_x()
-                                    .center_y()
-                                )
-                                .push(Container::new(Column::new()
-                                        .push(numerator_b)
-                                        .push(Container::new(line_b).padding(Padding{top: 20.0, right: 0.0, bottom: 20.0, left: 0.0}))
-                                        .push(Tooltip::new(denominator_b, inserted, tooltip::Position::Bottom))
-                                        .align_items(Alignment::Center)
-                                    ).height(Length::Fill)
-                                    .align_y(Vertical::Center)
-                                    .align_x(Horizontal::Left)
-                                ))
-                                .width(Length::Fill)
-                                .height(Length::Fill)
-                                .align_x(Horizontal::Center)
-                                .align_y(Vertical::Center)
-                            )
-                            .push(Container::new(if self.exercise_count <20 {next_button} else {finish_button})
-                                .width(Length::Fill)
-                                .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 450.0})
-                            )
-                            .width(Length::Fill)
-                            .height(Length::Fill)
-                            .align_items(Alignment::Center)
-                            .into()
-                    }
-                    _ => {
-                        panic!("Error: input field different from expected [0, 1, 2, 3]");
-                    }
-                }
+                    )
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_items(Alignment::Center)
+                    .into()
             }
             Mode::FinalEvaluation => {
 
@@ -708,12 +863,14 @@ impl Application for State{
                         .push(
                             Container::new(
                                 Column::new()
+                                    .push(Container::new(headline).padding(Padding{top: 0.0, right: 0.0, bottom: 20.0, left: 0.0}))
                                     .push(Container::new(
                                         Row::new()
                                             .push(Container::new(result_labels).align_x(Horizontal::Left).padding(Padding{top: 0.0, right: 50.0, bottom: 0.0, left: 0.0}))
                                             .push(Container::new(result_numbers).align_x(Horizontal::Right))
                                     ).padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 0.0}))
                                     .push(Container::new(focus_text).padding(Padding{top: 0.0, right: 0.0, bottom: 0.0, left: 0.0}))
+                                    .push(Container::new(seed_text).padding(Padding{top: 20.0, right: 0.0, bottom: 0.0, left: 0.0}))
                             )
                             .align_y(Vertical::Center)
                             .align_x(Horizontal::Center)
@@ -722,13 +879,14 @@ impl Application for State{
                         )
                         .push(
                             Container::new(Row::new()
-                                .push(Container::new(quit_button)
-                                    .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 50.0})
-                                )
-                                .push(Container::new(restart_button)
-                                    .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 280.0})
-                                )
-                            ).align_x(Horizontal::Left)
+                                .push(quit_button)
+                                .push(review_button)
+                                .push(export_button)
+                                .push(retry_mistakes_button)
+                                .push(restart_button)
+                                .spacing(16)
+                            ).padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 16.0})
+                            .align_x(Horizontal::Left)
                             .width(Length::Fill)
                         )
                         .height(Length::Fill)
@@ -740,11 +898,13 @@ impl Application for State{
                         .push(
                             Container::new(
                                 Column::new()
+                                    .push(Container::new(headline).padding(Padding{top: 0.0, right: 0.0, bottom: 20.0, left: 0.0}))
                                     .push(Container::new(
                                         Row::new()
                                             .push(Container::new(result_labels).align_x(Horizontal::Left).padding(Padding{top: 0.0, right: 50.0, bottom: 0.0, left: 0.0}))
                                             .push(Container::new(result_numbers).align_x(Horizontal::Right))
                                     ).padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 0.0}))
+                                    .push(Container::new(seed_text))
                             )
                             .align_y(Vertical::Center)
                             .align_x(Horizontal::Center)
@@ -753,13 +913,14 @@ impl Application for State{
                         )
                         .push(
                             Container::new(Row::new()
-                                .push(Container::new(quit_button)
-                                    .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 50.0})
-                                )
-                                .push(Container::new(restart_button)
-                                    .padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 280.0})
-                                )
-                            ).align_x(Horizontal::Left)
+                                .push(quit_button)
+                                .push(review_button)
+                                .push(export_button)
+                                .push(retry_mistakes_button)
+                                .push(restart_button)
+                                .spacing(16)
+                            ).padding(Padding{top: 0.0, right: 0.0, bottom: 30.0, left: 16.0})
+                            .align_x(Horizontal::Left)
                             .width(Length::Fill)
                         )
                         .height(Length::Fill)
@@ -770,6 +931,13 @@ impl Application for State{
         }
 
     }
+    fn subscription(&self) -> Subscription<Self::Message> {
+        if self.session.remaining_time().is_some() && self.session.mode() != Mode::FinalEvaluation {
+            time::every(Duration::from_secs(1)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        }
+    }
     fn theme(&self) -> Self::Theme {
         Theme::Light
     }
@@ -782,19 +950,67 @@ const ICON_WIDTH: u32 = 512;
 
 fn main() -> iced::Result {
 
+    let cli = Cli::parse();
+    if let Some(command) = &cli.command {
+        let (store, settings) = Store::open(&cli);
+        let written = match command {
+            // a history that could not be read has nothing to report
+            CliCommand::Report{..} | CliCommand::Progress{..} if store.path.is_none() => {Err(store.error.clone().unwrap_or_default())}
+            CliCommand::Worksheet{problems, output} => {Worksheet::generate(settings, (*problems).max(1)).export(output).map_err(|error| error.to_string())}
+            CliCommand::Report{last, output} => {
+                store.report(*last)
+                    .map_err(|error| error.to_string())
+                    .and_then(|report| report.export(output).map_err(|error| error.to_string()))
+            }
+            CliCommand::Progress{last, all, output} => {
+                let names = if *all {store.history.names()} else {vec![store.name()]};
+                names.iter()
+                    .map(|name| {
+                        let file = if *all {output.join(format!("{}.html", file_name(name)))} else {output.clone()};
+                        store.progress(name, *last)
+                            .map_err(|error| error.to_string())
+                            .and_then(|progress| progress.export(&file).map_err(|error| error.to_string()))
+                    })
+                    .collect()
+            }
+        };
+        match written {
+            Ok(written) => {written.iter().for_each(|file| println!("{}", file.display()))}
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if cli.tui || cli.batch {
+        let result = if cli.batch {
+            let (store, settings) = Store::open(&cli);
+            batch::run(store, settings, io::stdin().lock(), io::stdout().lock())
+        } else {
+            tui::run(&cli)
+        };
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let image = image::load_from_memory(ICON).unwrap();
     let icon = window::icon::from_rgba(image.as_bytes().to_vec(), ICON_HEIGHT, ICON_WIDTH).unwrap();
 
     let settings = Settings{
         id: None,
         window: window::settings::Settings{
-            size: Size { width: 600.0, height: 400.0 },
+            size: Size { width: 600.0, height: 440.0 },
             position: window::Position::Centered,
             resizable: false,
             icon: Some(icon),
             ..Default::default()
         },
         antialiasing: true,
+        flags: cli,
         ..Default::default()
     };
 
@@ -0,0 +1,974 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use crate::difficulty::Ranges;
+use crate::engine::{draw, Exercise};
+use crate::rational::Rational;
+use crate::weakness::Weakness;
+
+/// Least common multiple, the common denominator of fractions over `a` and `b`.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd64(a, b) * b
+}
+
+fn gcd64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Greatest common divisor, `gcd(a, 0)` is `a`.
+pub fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fraction {
+    pub num: u32,
+    pub den: u32,
+}
+impl Fraction {
+    pub const fn new(num: u32, den: u32) -> Self {
+        Fraction{num, den}
+    }
+    pub fn is_reduced(&self) -> bool {
+        gcd(self.num, self.den) == 1
+    }
+    /// The same fraction in lowest terms.
+    pub fn reduced(&self) -> Self {
+        let divisor = gcd(self.num, self.den).max(1);
+        Fraction{num: self.num/divisor, den: self.den/divisor}
+    }
+    pub fn scaled(&self, multiplier: u32) -> Self {
+        Fraction{num: self.num*multiplier, den: self.den*multiplier}
+    }
+    pub fn is_equivalent(&self, other: &Fraction) -> bool {
+        self.compare(other) == Ordering::Equal
+    }
+    /// Compares the values, not the terms.
+    pub fn compare(&self, other: &Fraction) -> Ordering {
+        (self.num as u64 * other.den as u64).cmp(&(other.num as u64 * self.den as u64))
+    }
+}
+impl Display for Fraction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+/// `whole fraction`, like `2 1/3`; the fraction is normally proper.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MixedNumber {
+    pub whole: u32,
+    pub fraction: Fraction,
+}
+impl MixedNumber {
+    pub fn from_improper(fraction: Fraction) -> Self {
+        MixedNumber{whole: fraction.num / fraction.den, fraction: Fraction::new(fraction.num % fraction.den, fraction.den)}
+    }
+    pub fn improper(&self) -> Fraction {
+        Fraction::new(self.whole * self.fraction.den + self.fraction.num, self.fraction.den)
+    }
+}
+impl Display for MixedNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.whole, self.fraction)
+    }
+}
+
+/// The kind of problems a session serves; `Mixed` draws each problem from all the others.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    #[default]
+    MissingTerm,
+    /// Missing-term problems where the known fraction is the big one.
+    ScaleDown,
+    Simplify,
+    Equivalent,
+    Chain,
+    Compare,
+    Multiplier,
+    MixedNumber,
+    Decimal,
+    Mixed,
+}
+impl Kind {
+    pub const ALL: [Kind; 10] = [Kind::MissingTerm, Kind::ScaleDown, Kind::Simplify, Kind::Equivalent, Kind::Chain, Kind::Compare, Kind::Multiplier, Kind::MixedNumber, Kind::Decimal, Kind::Mixed];
+    /// The kinds `Mixed` draws from.
+    pub const SINGLE: [Kind; 9] = [Kind::MissingTerm, Kind::ScaleDown, Kind::Simplify, Kind::Equivalent, Kind::Chain, Kind::Compare, Kind::Multiplier, Kind::MixedNumber, Kind::Decimal];
+}
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::MissingTerm   => {write!(f, "Missing term")},
+            Kind::ScaleDown     => {write!(f, "Scale down")},
+            Kind::Simplify      => {write!(f, "Simplify")},
+            Kind::Equivalent    => {write!(f, "Equivalent?")},
+            Kind::Chain         => {write!(f, "Chain")},
+            Kind::Compare       => {write!(f, "Compare")},
+            Kind::Multiplier    => {write!(f, "Find multiplier")},
+            Kind::MixedNumber   => {write!(f, "Mixed numbers")},
+            Kind::Decimal       => {write!(f, "Decimals")},
+            Kind::Mixed         => {write!(f, "Mixed")},
+        }
+    }
+}
+impl FromStr for Kind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Kind::ALL.into_iter()
+            .find(|kind| kind.to_string().replace(' ', "-").replace('?', "").eq_ignore_ascii_case(s))
+            .ok_or(format!("unknown exercise kind '{}', expected one of missing-term, scale-down, simplify, equivalent, chain, compare, find-multiplier, mixed-numbers, decimals, mixed", s))
+    }
+}
+
+/// A wrong way of scaling a fraction, used to build pairs that look equivalent but are not.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Misconception {
+    /// `2/3 = 4/5`: the same number added to both terms.
+    AddedInsteadOfMultiplied,
+    /// `2/3 = 6/3`: only the numerator or only the denominator multiplied.
+    OneTermOnly,
+    /// `2/3 = 6/4`: the scaled fraction turned upside down.
+    Flipped,
+    /// `2/3 = 4/9`: the terms multiplied by different numbers.
+    DifferentMultipliers,
+}
+impl Misconception {
+    pub const ALL: [Misconception; 4] = [Misconception::AddedInsteadOfMultiplied, Misconception::OneTermOnly, Misconception::Flipped, Misconception::DifferentMultipliers];
+
+    /// `fraction` scaled by `multiplier` the wrong way.
+    pub fn apply<R: Rng>(&self, rng: &mut R, fraction: Fraction, multiplier: u32) -> Fraction {
+        match self {
+            Misconception::AddedInsteadOfMultiplied => {Fraction::new(fraction.num + multiplier, fraction.den + multiplier)}
+            Misconception::OneTermOnly if rng.gen_bool(0.5) => {Fraction::new(fraction.num * multiplier, fraction.den)}
+            Misconception::OneTermOnly => {Fraction::new(fraction.num, fraction.den * multiplier)}
+            Misconception::Flipped => {Fraction::new(fraction.den * multiplier, fraction.num * multiplier)}
+            Misconception::DifferentMultipliers => {Fraction::new(fraction.num * multiplier, fraction.den * (multiplier + 1))}
+        }
+    }
+}
+impl Display for Misconception {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Misconception::AddedInsteadOfMultiplied => {write!(f, "added instead of multiplied")},
+            Misconception::OneTermOnly              => {write!(f, "one term only")},
+            Misconception::Flipped                  => {write!(f, "flipped")},
+            Misconception::DifferentMultipliers     => {write!(f, "different multipliers")},
+        }
+    }
+}
+
+/// Is `a = b`? `distractor` tells how `b` was spoilt when they are not equivalent.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Equivalence {
+    pub a: Fraction,
+    pub b: Fraction,
+    pub distractor: Option<Misconception>,
+}
+impl Equivalence {
+    /// An equivalent pair half of the time, a pair built on a random misconception otherwise.
+    pub fn generate<R: Rng>(rng: &mut R, base: Fraction, multiplier: u32) -> Self {
+        let distractor = if rng.gen_bool(0.5) {Some(Misconception::ALL[rng.gen_range(0..Misconception::ALL.len())])} else {None};
+        let scaled = match distractor {
+            Some(misconception) => {misconception.apply(rng, base, multiplier)}
+            None => {base.scaled(multiplier)}
+        };
+        // adding to or flipping a fraction equal to 1 keeps it equivalent, spoil it for sure
+        let (scaled, distractor) = match distractor {
+            Some(_) if scaled.is_equivalent(&base) => {
+                (Misconception::DifferentMultipliers.apply(rng, base, multiplier), Some(Misconception::DifferentMultipliers))
+            }
+            _ => {(scaled, distractor)}
+        };
+        let (a, b) = if rng.gen_bool(0.5) {(base, scaled)} else {(scaled, base)};
+        Equivalence{a, b, distractor}
+    }
+    pub fn is_equivalent(&self) -> bool {
+        self.a.is_equivalent(&self.b)
+    }
+}
+
+/// The longest chain of equivalent fractions.
+pub const MAX_CHAIN: usize = 4;
+
+/// `fractions[0] = fractions[1] = ...` over the first `len` fractions, each one after the first with a hidden term.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chain {
+    pub fractions: [Fraction; MAX_CHAIN],
+    pub len: usize,
+    /// Whether the numerator rather than the denominator of each fraction is hidden, ignored for the first one.
+    pub hidden_numerators: [bool; MAX_CHAIN],
+}
+impl Chain {
+    /// Three or four multiples of `base`, scaled up and down along the chain, the first one being `base` itself
+    /// or one of its multiples by `ranges.multiplier`.
+    pub fn generate<R: Rng>(rng: &mut R, base: Fraction, ranges: Ranges) -> Self {
+        let multipliers = ranges.multiplier;
+        let mut candidates: Vec<u32> = std::iter::once(1).chain(multipliers.min..=multipliers.max).collect();
+        // narrow custom ranges: take the multipliers below the range, then above it while the terms stay
+        // answerable, rather than repeat a fraction; only two fractions are left when even that is not enough
+        let biggest = (ranges.input_limit() - 1) / base.num.max(base.den).max(1);
+        let outside: Vec<u32> = (2..multipliers.min).rev().chain(multipliers.max + 1..=biggest).take(MAX_CHAIN.saturating_sub(candidates.len())).collect();
+        candidates.extend(outside);
+        let len = rng.gen_range(3..=MAX_CHAIN).min(candidates.len());
+        candidates.shuffle(rng);
+        candidates.truncate(len);
+        // make sure the chain goes both up and down
+        if candidates.windows(2).all(|pair| pair[0] < pair[1]) || candidates.windows(2).all(|pair| pair[0] > pair[1]) {
+            candidates.swap(len-2, len-1);
+        }
+        let mut fractions = [Fraction::default(); MAX_CHAIN];
+        let mut hidden_numerators = [false; MAX_CHAIN];
+        for (index, multiplier) in candidates.into_iter().enumerate() {
+            fractions[index] = base.scaled(multiplier);
+            hidden_numerators[index] = rng.gen_bool(0.5);
+        }
+        Chain{fractions, len, hidden_numerators}
+    }
+    pub fn fractions(&self) -> &[Fraction] {
+        &self.fractions[..self.len]
+    }
+    /// The hidden terms in reading order.
+    pub fn solution(&self) -> Vec<u32> {
+        (1..self.len)
+            .map(|index| if self.hidden_numerators[index] {self.fractions[index].num} else {self.fractions[index].den})
+            .collect()
+    }
+}
+impl Display for Chain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.fractions[0])?;
+        for index in 1..self.len {
+            let fraction = self.fractions[index];
+            if self.hidden_numerators[index] {
+                write!(f, " = ?/{}", fraction.den)?;
+            } else {
+                write!(f, " = {}/?", fraction.num)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The most fractions compared at once.
+pub const MAX_COMPARED: usize = 5;
+
+/// Answer to a comparison: 1 for `<`, 2 for `=` and 3 for `>`, leaving 0 for no answer.
+pub fn relation(ordering: Ordering) -> u32 {
+    match ordering {
+        Ordering::Less      => {1}
+        Ordering::Equal     => {2}
+        Ordering::Greater   => {3}
+    }
+}
+
+pub fn relation_symbol(answer: u32) -> &'static str {
+    match answer {
+        1 => {"<"}
+        2 => {"="}
+        3 => {">"}
+        _ => {"?"}
+    }
+}
+
+/// The first `len` fractions, to be compared two by two from left to right.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comparison {
+    pub fractions: [Fraction; MAX_COMPARED],
+    pub len: usize,
+}
+impl Comparison {
+    /// Three to five fractions of `ranges.number`, now and then an equivalent of an earlier one
+    /// scaled by `ranges.multiplier` so that `=` comes up too.
+    pub fn generate<R: Rng>(rng: &mut R, ranges: Ranges) -> Self {
+        let len = rng.gen_range(3..=MAX_COMPARED);
+        let mut fractions = [Fraction::default(); MAX_COMPARED];
+        for index in 0..len {
+            fractions[index] = if index > 0 && rng.gen_bool(0.25) {
+                // scale the lowest terms, so a multiple of a multiple cannot grow past the ranges squared
+                let earlier = fractions[rng.gen_range(0..index)].reduced();
+                earlier.scaled(rng.gen_range(ranges.multiplier.min..=ranges.multiplier.max))
+            } else {
+                let mut drawn = Fraction::default();
+                // the same fraction twice is no comparison, unless the range leaves no choice
+                for _ in 0..10 {
+                    drawn = Fraction::new(rng.gen_range(ranges.number.min..=ranges.number.max), rng.gen_range(ranges.number.min..=ranges.number.max));
+                    if !fractions[..index].contains(&drawn) {
+                        break;
+                    }
+                }
+                drawn
+            };
+        }
+        Comparison{fractions, len}
+    }
+    pub fn fractions(&self) -> &[Fraction] {
+        &self.fractions[..self.len]
+    }
+    /// The relation between each fraction and the next one.
+    pub fn solution(&self) -> Vec<u32> {
+        self.fractions().windows(2).map(|pair| relation(pair[0].compare(&pair[1]))).collect()
+    }
+    /// The least common denominator of the fractions.
+    pub fn common_denominator(&self) -> u64 {
+        self.fractions().iter().fold(1, |common, fraction| lcm(common, fraction.den as u64))
+    }
+    /// Each fraction rewritten over the common denominator, as `(numerator, multiplier)`.
+    pub fn hint(&self) -> Vec<(u64, u64)> {
+        let common = self.common_denominator();
+        self.fractions().iter()
+            .map(|fraction| {
+                let multiplier = common / fraction.den as u64;
+                (fraction.num as u64 * multiplier, multiplier)
+            })
+            .collect()
+    }
+    /// What went wrong when `answer` was chosen between the fractions `gap` and `gap+1`.
+    fn mistake(&self, gap: usize, answer: u32) -> Mistake {
+        let (a, b) = (self.fractions[gap], self.fractions[gap+1]);
+        if a.den != b.den && answer == relation(a.num.cmp(&b.num)) {
+            Mistake::ComparedNumerators
+        } else if a.den != b.den && answer == relation(a.den.cmp(&b.den)) {
+            Mistake::ComparedDenominators
+        } else {
+            Mistake::WrongRelation
+        }
+    }
+}
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fractions: Vec<String> = self.fractions().iter().map(|fraction| fraction.to_string()).collect();
+        write!(f, "{}", fractions.join(" ? "))
+    }
+}
+
+/// `from = to`, where the factor taking `from` to `to` is to be found; a fraction when it is not a whole number.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scaling {
+    pub from: Fraction,
+    pub to: Fraction,
+}
+impl Scaling {
+    /// A fraction and one of its multiples half of the time, two non-reduced equivalent fractions
+    /// that are not multiples of each other (`6/9 = 10/15`) otherwise.
+    pub fn generate<R: Rng>(rng: &mut R, ranges: Ranges, weakness: &Weakness, strength: f32) -> Self {
+        let base = Fraction::new(rng.gen_range(ranges.number.min..=ranges.number.max), rng.gen_range(ranges.number.min..=ranges.number.max));
+        let multiplier = draw(rng, ranges.multiplier, &weakness.multipliers, strength);
+        let other = rng.gen_range(ranges.multiplier.min..=ranges.multiplier.max);
+        if rng.gen_bool(0.5) && !multiplier.is_multiple_of(other) {
+            Scaling{from: base.scaled(other), to: base.scaled(multiplier)}
+        } else {
+            Scaling{from: base, to: base.scaled(multiplier)}
+        }
+    }
+    /// The factor in lowest terms.
+    pub fn ratio(&self) -> Fraction {
+        Fraction::new(self.to.num, self.from.num).reduced()
+    }
+}
+
+/// A mixed number next to an equivalent improper fraction `multiplier` times bigger in its terms,
+/// the student writing one as the other.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conversion {
+    pub mixed: MixedNumber,
+    pub multiplier: u32,
+    /// `2 1/3 = ?/6` when set, `14/6 = ? ?/3` otherwise.
+    pub to_improper: bool,
+}
+impl Conversion {
+    pub fn generate<R: Rng>(rng: &mut R, ranges: Ranges, weakness: &Weakness, strength: f32) -> Self {
+        // a proper fractional part needs a denominator of at least 2
+        let den = rng.gen_range(ranges.number.min.max(2)..=ranges.number.max.max(2));
+        let fraction = Fraction::new(rng.gen_range(1..den), den);
+        // the improper numerator (whole*den + num)*multiplier must stay answerable, with a whole part of 1 at least
+        let limit = ranges.input_limit() - 1;
+        let multiplier = draw(rng, ranges.multiplier, &weakness.multipliers, strength).min(limit / (den + fraction.num)).max(1);
+        let whole = rng.gen_range(1..=ranges.multiplier.max.min((limit / multiplier - fraction.num) / den));
+        Conversion{mixed: MixedNumber{whole, fraction}, multiplier, to_improper: rng.gen_bool(0.5)}
+    }
+    pub fn improper(&self) -> Fraction {
+        self.mixed.improper().scaled(self.multiplier)
+    }
+}
+
+/// The denominators that divide a power of ten up to 1000, the only ones giving a finite decimal here.
+const DECIMAL_DENOMINATORS: [u32; 14] = [2, 4, 5, 8, 10, 20, 25, 40, 50, 100, 125, 200, 250, 500];
+
+/// `fraction = ?/power = ? = ?%`: a proper fraction over a power of ten, as a decimal and as a percentage.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecimalForm {
+    pub fraction: Fraction,
+    /// 10, 100 or 1000, the smallest power of ten the denominator divides.
+    pub power: u32,
+}
+impl DecimalForm {
+    /// A proper fraction over one of the denominators the ranges reach.
+    pub fn generate<R: Rng>(rng: &mut R, ranges: Ranges) -> Self {
+        let reachable: Vec<u32> = DECIMAL_DENOMINATORS.into_iter().filter(|den| *den <= ranges.max_term().max(10)).collect();
+        let den = reachable[rng.gen_range(0..reachable.len())];
+        let fraction = Fraction::new(rng.gen_range(1..den), den);
+        let power = [10, 100, 1000].into_iter().find(|power| power % den == 0).unwrap_or(1000);
+        DecimalForm{fraction, power}
+    }
+    pub fn value(&self) -> Rational {
+        Rational::new(self.fraction.num as i64, self.fraction.den as i64)
+    }
+    /// The numerator over `power`, the decimal and the percentage.
+    pub fn solution(&self) -> Vec<Rational> {
+        let value = self.value();
+        vec![value.scaled(self.power as i64), value, value.scaled(100)]
+    }
+    /// What went wrong in each blank, `None` for the right ones.
+    fn mistakes(&self, answers: &[Rational]) -> Vec<Option<Mistake>> {
+        let solution = self.solution();
+        solution.iter().enumerate()
+            .map(|(blank, expected)| {
+                let answer = answers.get(blank).copied().unwrap_or_default();
+                if answer == *expected {
+                    None
+                } else if blank == 0 {
+                    Some(Mistake::CannotMultiply)
+                } else if [10, 100, 1000].into_iter().any(|power| answer == expected.scaled(power) || answer == expected.divided(power)) {
+                    Some(Mistake::DecimalPlace)
+                } else {
+                    Some(Mistake::WrongDecimal)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Why an answer was wrong, tallied separately in the evaluation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Mistake {
+    WrongTerm,
+    /// Missed the term of the smaller fraction, found by dividing.
+    CannotDivide,
+    /// Missed the term of the bigger fraction, found by multiplying.
+    CannotMultiply,
+    /// Equivalent to the fraction to simplify, but not in lowest terms.
+    NotReduced,
+    /// Not even equivalent to the fraction to simplify.
+    NotEquivalent,
+    /// Took a pair built on the misconception for equivalent.
+    Believed(Misconception),
+    /// Took an equivalent pair for different.
+    Rejected,
+    /// Ordered fractions over different denominators by their numerators alone.
+    ComparedNumerators,
+    /// Took the fraction with the bigger denominator for the bigger one.
+    ComparedDenominators,
+    WrongRelation,
+    /// Gave the factor taking the second fraction back to the first one.
+    InvertedRatio,
+    WrongMultiplier,
+    /// Left out the whole part, or added it to the numerator as it is.
+    WholePartSlip,
+    WrongConversion,
+    /// Off by a power of ten, the decimal point in the wrong place.
+    DecimalPlace,
+    WrongDecimal,
+    /// Found the size of a signed term but not its sign.
+    WrongSign,
+}
+impl Mistake {
+    /// What to work on after making this mistake repeatedly, if there is more to say than the times tables.
+    pub fn advice(&self) -> Option<&'static str> {
+        match self {
+            Mistake::WrongTerm      => {None}
+            Mistake::CannotDivide   => {Some("Practise scaling fractions down: divide both terms by the same number")}
+            Mistake::CannotMultiply => {Some("Practise scaling fractions up: multiply both terms by the same number")}
+            Mistake::NotReduced     => {Some("Keep dividing until the fraction cannot be reduced any further")}
+            Mistake::NotEquivalent  => {Some("Divide the numerator and the denominator by the same number")}
+            Mistake::Believed(Misconception::AddedInsteadOfMultiplied)  => {Some("adding the same number to both terms changes the fraction, multiply them instead")}
+            Mistake::Believed(Misconception::OneTermOnly)               => {Some("multiply both the numerator and the denominator, not just one of them")}
+            Mistake::Believed(Misconception::Flipped)                   => {Some("flipping a fraction changes its value")}
+            Mistake::Believed(Misconception::DifferentMultipliers)      => {Some("the numerator and the denominator must be multiplied by the same number")}
+            Mistake::Rejected       => {Some("Simplify both fractions to check whether they are equivalent")}
+            Mistake::ComparedNumerators     => {Some("Rewrite the fractions over a common denominator before comparing the numerators")}
+            Mistake::ComparedDenominators   => {Some("A bigger denominator means smaller parts, compare over a common denominator")}
+            Mistake::WrongRelation  => {Some("Use the hint to rewrite the fractions over a common denominator")}
+            Mistake::InvertedRatio  => {Some("The multiplier takes the first fraction to the second: divide the second term by the first")}
+            Mistake::WrongMultiplier => {None}
+            Mistake::WholePartSlip  => {Some("Each whole is worth the denominator: multiply the whole part by it before adding the numerator")}
+            Mistake::WrongConversion => {Some("Divide the numerator by the denominator: the quotient is the whole part and the remainder the new numerator")}
+            Mistake::DecimalPlace   => {Some("Count the decimal places: tenths, hundredths, thousandths")}
+            Mistake::WrongDecimal   => {Some("Rewrite the fraction over 100 first: a percentage is a number of hundredths")}
+            Mistake::WrongSign      => {Some("One minus sign makes a fraction negative, wherever it is: -2/3 = 2/-3, and two cancel out")}
+        }
+    }
+}
+impl Display for Mistake {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mistake::WrongTerm      => {write!(f, "wrong term")},
+            Mistake::CannotDivide   => {write!(f, "wrong when dividing")},
+            Mistake::CannotMultiply => {write!(f, "wrong when multiplying")},
+            Mistake::NotReduced     => {write!(f, "not fully reduced")},
+            Mistake::NotEquivalent  => {write!(f, "not equivalent")},
+            Mistake::Believed(misconception) => {write!(f, "believed {}", misconception)},
+            Mistake::Rejected       => {write!(f, "rejected equivalent")},
+            Mistake::ComparedNumerators     => {write!(f, "compared numerators only")},
+            Mistake::ComparedDenominators   => {write!(f, "compared denominators only")},
+            Mistake::WrongRelation  => {write!(f, "wrong comparison")},
+            Mistake::InvertedRatio  => {write!(f, "inverted multiplier")},
+            Mistake::WrongMultiplier => {write!(f, "wrong multiplier")},
+            Mistake::WholePartSlip  => {write!(f, "whole part mishandled")},
+            Mistake::WrongConversion => {write!(f, "wrong conversion")},
+            Mistake::DecimalPlace   => {write!(f, "decimal point misplaced")},
+            Mistake::WrongDecimal   => {write!(f, "wrong decimal")},
+            Mistake::WrongSign      => {write!(f, "wrong sign")},
+        }
+    }
+}
+
+/// How the answer to a blank is written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Notation {
+    /// A whole number.
+    Whole,
+    /// A decimal number, `0.75` or `0,75`.
+    Decimal,
+    /// A number of hundredths, with or without its `%` sign.
+    Percent,
+}
+
+/// One problem of a session; answers fill its blanks in reading order, numerators before denominators,
+/// yes or no questions take a single answer, 1 for yes and 0 for no, comparisons one `relation` per pair.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Problem {
+    /// Find the hidden term of two equivalent fractions.
+    MissingTerm(Exercise),
+    /// Reduce the fraction to lowest terms.
+    Simplify(Fraction),
+    /// Tell whether two fractions are equivalent.
+    Equivalent(Equivalence),
+    /// Find a hidden term in every fraction of a chain.
+    Chain(Chain),
+    /// Tell whether each fraction is smaller than, equal to or bigger than the next one.
+    Compare(Comparison),
+    /// Find the factor linking two equivalent fractions, as a whole number or as a numerator and a denominator.
+    Multiplier(Scaling),
+    /// Write a mixed number as an improper fraction over a given denominator, or the other way round
+    /// with the whole part and the numerator to find.
+    MixedNumber(Conversion),
+    /// Write a fraction over a power of ten, as a decimal and as a percentage.
+    Decimal(DecimalForm),
+}
+impl Default for Problem {
+    fn default() -> Self {
+        Problem::MissingTerm(Exercise::default())
+    }
+}
+impl Problem {
+    /// A problem of `kind`, biased toward `weakness` like `Exercise::generate_adaptive`.
+    pub fn generate<R: Rng>(rng: &mut R, kind: Kind, ranges: Ranges, weakness: &Weakness, strength: f32) -> Self {
+        match kind {
+            Kind::MissingTerm => {Problem::MissingTerm(Exercise::generate_adaptive(rng, ranges, weakness, strength))}
+            Kind::ScaleDown => {Problem::MissingTerm(Exercise::scaled_down(rng, ranges, weakness, strength))}
+            Kind::Simplify => {
+                let multiplier = draw(rng, ranges.multiplier, &weakness.multipliers, strength);
+                let base = Fraction::new(rng.gen_range(ranges.number.min..=ranges.number.max), rng.gen_range(ranges.number.min..=ranges.number.max));
+                Problem::Simplify(base.reduced().scaled(multiplier))
+            }
+            Kind::Equivalent => {
+                let multiplier = draw(rng, ranges.multiplier, &weakness.multipliers, strength);
+                let base = Fraction::new(rng.gen_range(ranges.number.min..=ranges.number.max), rng.gen_range(ranges.number.min..=ranges.number.max));
+                Problem::Equivalent(Equivalence::generate(rng, base, multiplier))
+            }
+            Kind::Chain => {
+                let base = Fraction::new(rng.gen_range(ranges.number.min..=ranges.number.max), rng.gen_range(ranges.number.min..=ranges.number.max));
+                Problem::Chain(Chain::generate(rng, base.reduced(), ranges))
+            }
+            Kind::Compare => {Problem::Compare(Comparison::generate(rng, ranges))}
+            Kind::Multiplier => {Problem::Multiplier(Scaling::generate(rng, ranges, weakness, strength))}
+            Kind::MixedNumber => {Problem::MixedNumber(Conversion::generate(rng, ranges, weakness, strength))}
+            Kind::Decimal => {Problem::Decimal(DecimalForm::generate(rng, ranges))}
+            Kind::Mixed => {
+                let kind = Kind::SINGLE[rng.gen_range(0..Kind::SINGLE.len())];
+                Problem::generate(rng, kind, ranges, weakness, strength)
+            }
+        }
+    }
+    /// The kind of the problem, `MissingTerm` for scale-down problems too.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Problem::MissingTerm(_) => {Kind::MissingTerm}
+            Problem::Simplify(_)    => {Kind::Simplify}
+            Problem::Equivalent(_)  => {Kind::Equivalent}
+            Problem::Chain(_)       => {Kind::Chain}
+            Problem::Compare(_)     => {Kind::Compare}
+            Problem::Multiplier(_)  => {Kind::Multiplier}
+            Problem::MixedNumber(_) => {Kind::MixedNumber}
+            Problem::Decimal(_)     => {Kind::Decimal}
+        }
+    }
+    /// How many answers the problem takes.
+    pub fn blanks(&self) -> usize {
+        self.solution().len()
+    }
+    pub fn solution(&self) -> Vec<Rational> {
+        match self {
+            Problem::MissingTerm(exercise) => {vec![Rational::whole(exercise.signed_term(exercise.input_field_number))]}
+            Problem::Decimal(form) => {form.solution()}
+            _ => {self.whole_solution().into_iter().map(Rational::from).collect()}
+        }
+    }
+    /// How the answer to `blank` is written: a whole number but for the decimal and the percentage of a decimal problem.
+    pub fn notation(&self, blank: usize) -> Notation {
+        match (self, blank) {
+            (Problem::Decimal(_), 1) => {Notation::Decimal}
+            (Problem::Decimal(_), 2) => {Notation::Percent}
+            _ => {Notation::Whole}
+        }
+    }
+    /// Whether `answers` solve the problem, or what went wrong.
+    pub fn grade(&self, answers: &[Rational]) -> Result<(), Mistake> {
+        match (self, answers) {
+            (Problem::MissingTerm(_), [answer]) if *answer == self.solution()[0] => {Ok(())}
+            (Problem::MissingTerm(_), [answer]) if answer.abs() == self.solution()[0].abs() => {Err(Mistake::WrongSign)}
+            (Problem::MissingTerm(exercise), _) if exercise.is_dividing() => {Err(Mistake::CannotDivide)}
+            (Problem::MissingTerm(_), _) => {Err(Mistake::CannotMultiply)}
+            (Problem::Decimal(form), _) => {form.mistakes(answers).into_iter().flatten().next().map_or(Ok(()), Err)}
+            _ => {
+                let wholes: Vec<u32> = answers.iter().map(|answer| answer.to_u32().unwrap_or(0)).collect();
+                self.grade_whole(&wholes)
+            }
+        }
+    }
+    /// The solution of the problems answered with positive whole numbers, all but missing terms and decimals.
+    fn whole_solution(&self) -> Vec<u32> {
+        match self {
+            Problem::Simplify(fraction) => {
+                let reduced = fraction.reduced();
+                vec![reduced.num, reduced.den]
+            }
+            Problem::Equivalent(equivalence) => {vec![equivalence.is_equivalent() as u32]}
+            Problem::Chain(chain) => {chain.solution()}
+            Problem::Compare(comparison) => {comparison.solution()}
+            Problem::Multiplier(scaling) => {
+                let ratio = scaling.ratio();
+                if ratio.den == 1 {vec![ratio.num]} else {vec![ratio.num, ratio.den]}
+            }
+            Problem::MixedNumber(conversion) if conversion.to_improper => {vec![conversion.improper().num]}
+            Problem::MixedNumber(conversion) => {vec![conversion.mixed.whole, conversion.mixed.fraction.num]}
+            Problem::MissingTerm(_) | Problem::Decimal(_) => {Vec::new()}
+        }
+    }
+    fn grade_whole(&self, answers: &[u32]) -> Result<(), Mistake> {
+        match (self, answers) {
+            (Problem::Simplify(fraction), [num, den]) if *den != 0 && Fraction::new(*num, *den).is_equivalent(fraction) => {
+                if Fraction::new(*num, *den).is_reduced() {Ok(())} else {Err(Mistake::NotReduced)}
+            }
+            (Problem::Simplify(_), _) => {Err(Mistake::NotEquivalent)}
+            (Problem::Equivalent(equivalence), [answer]) if (*answer == 1) == equivalence.is_equivalent() => {Ok(())}
+            (Problem::Equivalent(equivalence), _) => {
+                match equivalence.distractor {
+                    Some(misconception) => {Err(Mistake::Believed(misconception))}
+                    None => {Err(Mistake::Rejected)}
+                }
+            }
+            (Problem::Chain(chain), _) if answers == chain.solution() => {Ok(())}
+            (Problem::Chain(_), _) => {Err(Mistake::WrongTerm)}
+            (Problem::Compare(comparison), _) => {
+                let solution = comparison.solution();
+                match (0..solution.len()).find(|gap| answers.get(*gap) != Some(&solution[*gap])) {
+                    Some(gap) => {Err(comparison.mistake(gap, answers.get(gap).copied().unwrap_or(0)))}
+                    None => {Ok(())}
+                }
+            }
+            (Problem::Multiplier(scaling), _) => {
+                let ratio = scaling.ratio();
+                let answer = match answers {
+                    [num] => {Fraction::new(*num, 1)}
+                    [num, den] if *den != 0 => {Fraction::new(*num, *den)}
+                    _ => {return Err(Mistake::WrongMultiplier)}
+                };
+                if answer.is_equivalent(&ratio) {
+                    Ok(())
+                } else if answer.is_equivalent(&Fraction::new(ratio.den, ratio.num)) {
+                    Err(Mistake::InvertedRatio)
+                } else {
+                    Err(Mistake::WrongMultiplier)
+                }
+            }
+            (Problem::MixedNumber(_), _) if answers == self.whole_solution() => {Ok(())}
+            (Problem::MixedNumber(conversion), [numerator]) => {
+                let MixedNumber{whole, fraction} = conversion.mixed;
+                let slips = [fraction.num * conversion.multiplier, (whole + fraction.num) * conversion.multiplier];
+                if slips.contains(numerator) {Err(Mistake::WholePartSlip)} else {Err(Mistake::WrongConversion)}
+            }
+            (Problem::MixedNumber(_), _) => {Err(Mistake::WrongConversion)}
+            (Problem::MissingTerm(_), _) | (Problem::Decimal(_), _) => {Err(Mistake::WrongTerm)}
+        }
+    }
+    /// Whether each blank was filled right; the terms of a simplification are only right together.
+    pub fn grade_blanks(&self, answers: &[Rational]) -> Vec<bool> {
+        match self {
+            Problem::Decimal(form) => {form.mistakes(answers).iter().map(Option::is_none).collect()}
+            Problem::Chain(_) | Problem::Compare(_) | Problem::MixedNumber(_) => {
+                self.solution().iter().enumerate().map(|(blank, solution)| answers.get(blank) == Some(solution)).collect()
+            }
+            _ => {vec![self.grade(answers).is_ok(); self.solution().len()]}
+        }
+    }
+    /// The multiplier linking the fractions, the common factor to divide away when simplifying;
+    /// `None` when the fractions are not equivalent.
+    pub fn multiplier(&self) -> Option<u32> {
+        match self {
+            Problem::MissingTerm(exercise) => {Some(exercise.factors().0)}
+            Problem::Simplify(fraction) => {Some(gcd(fraction.num, fraction.den))}
+            Problem::Equivalent(equivalence) if equivalence.is_equivalent() => {
+                let (a, b) = (equivalence.a.num, equivalence.b.num);
+                Some(a.max(b) / a.min(b))
+            }
+            Problem::Multiplier(scaling) if scaling.ratio().den == 1 => {Some(scaling.ratio().num)}
+            Problem::MixedNumber(conversion) => {Some(conversion.multiplier)}
+            Problem::Equivalent(_) | Problem::Chain(_) | Problem::Compare(_) | Problem::Multiplier(_) | Problem::Decimal(_) => {None}
+        }
+    }
+    /// The same problem asked differently, when its kind allows it.
+    pub fn with_other_hidden<R: Rng>(&self, rng: &mut R) -> Self {
+        match self {
+            Problem::MissingTerm(exercise) => {Problem::MissingTerm(exercise.with_other_hidden(rng))}
+            Problem::Simplify(_) | Problem::Equivalent(_) | Problem::Chain(_) | Problem::Compare(_) | Problem::Multiplier(_) | Problem::MixedNumber(_) | Problem::Decimal(_) => {*self}
+        }
+    }
+    /// `answers` written the way the problem asks for them.
+    pub fn format_answers(&self, answers: &[Rational]) -> String {
+        let answer = |blank: usize| answers.get(blank).copied().unwrap_or_default();
+        match self {
+            Problem::Equivalent(_) => {String::from(if answers == [Rational::whole(1)] {"yes"} else {"no"})}
+            Problem::MixedNumber(conversion) if !conversion.to_improper => {
+                format!("{} {}/{}", answer(0), answer(1), conversion.mixed.fraction.den)
+            }
+            Problem::Decimal(form) => {format!("{}/{}, {}, {}%", answer(0), form.power, answer(1), answer(2))}
+            Problem::Compare(_) => {
+                let symbols: Vec<&str> = answers.iter().map(|answer| relation_symbol(answer.to_u32().unwrap_or(0))).collect();
+                symbols.join(" ")
+            }
+            _ => {
+                let answers: Vec<String> = answers.iter().map(|answer| answer.to_string()).collect();
+                answers.join(if let Problem::Chain(_) = self {", "} else {"/"})
+            }
+        }
+    }
+}
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::MissingTerm(exercise)  => {write!(f, "{}", exercise)},
+            Problem::Simplify(fraction)     => {write!(f, "{} = ?/? in lowest terms", fraction)},
+            Problem::Equivalent(equivalence) => {write!(f, "{} = {} ?", equivalence.a, equivalence.b)},
+            Problem::Chain(chain)           => {write!(f, "{}", chain)},
+            Problem::Compare(comparison)    => {write!(f, "{}", comparison)},
+            Problem::Multiplier(scaling)    => {write!(f, "{} = {}, multiplied by ?", scaling.from, scaling.to)},
+            Problem::MixedNumber(conversion) if conversion.to_improper => {write!(f, "{} = ?/{}", conversion.mixed, conversion.improper().den)},
+            Problem::MixedNumber(conversion) => {write!(f, "{} = ? ?/{}", conversion.improper(), conversion.mixed.fraction.den)},
+            Problem::Decimal(form)          => {write!(f, "{} = ?/{} = ? = ?%", form.fraction, form.power)},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::difficulty::{Bounds, Difficulty};
+    use crate::engine::parse_answer;
+    use super::*;
+
+    /// The solution of `problem` typed in and parsed like an answer.
+    fn typed_solution(problem: &Problem, limit: u32) -> Vec<Rational> {
+        problem.solution().iter().enumerate()
+            .map(|(blank, solution)| parse_answer(problem, blank, &solution.to_string(), limit))
+            .collect()
+    }
+
+    fn wholes(answers: &[i64]) -> Vec<Rational> {
+        answers.iter().map(|answer| Rational::whole(*answer)).collect()
+    }
+
+    #[test]
+    fn decimals_use_the_smallest_power_of_ten() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..500 {
+            let Problem::Decimal(form) = Problem::generate(&mut rng, Kind::Decimal, Difficulty::Expert.ranges(None), &Weakness::default(), 0.0) else {
+                unreachable!()
+            };
+            let expected = match form.fraction.den {
+                2 | 5 | 10 => {10}
+                4 | 20 | 25 | 50 | 100 => {100}
+                _ => {1000}
+            };
+            assert_eq!(form.power, expected, "{}", form.fraction);
+        }
+    }
+
+    #[test]
+    fn every_solution_can_be_typed_in() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for difficulty in Difficulty::ALL {
+            let ranges = difficulty.ranges(None);
+            for kind in Kind::ALL {
+                for _ in 0..500 {
+                    let problem = Problem::generate(&mut rng, kind, ranges, &Weakness::default(), 0.0);
+                    assert_eq!(problem.grade(&typed_solution(&problem, ranges.input_limit())), Ok(()), "{} on {}", problem, difficulty);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chains_of_narrow_ranges_can_be_typed_in() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for ranges in [Ranges{multiplier: Bounds::new(50, 50), number: Bounds::new(1, 999)}, Ranges{multiplier: Bounds::new(2, 2), number: Bounds::new(1, 499)}] {
+            for _ in 0..1000 {
+                let problem = Problem::generate(&mut rng, Kind::Chain, ranges, &Weakness::default(), 0.0);
+                let Problem::Chain(chain) = problem else {
+                    unreachable!()
+                };
+                assert!(chain.len >= 2 && (1..chain.len).all(|index| !chain.fractions[..index].contains(&chain.fractions[index])), "{}", problem);
+                assert_eq!(problem.grade(&typed_solution(&problem, ranges.input_limit())), Ok(()), "{}", problem);
+            }
+        }
+    }
+
+    #[test]
+    fn simplifying_tells_unreduced_from_wrong_fractions() {
+        let problem = Problem::Simplify(Fraction::new(12, 16));
+        assert_eq!(problem.grade(&wholes(&[3, 4])), Ok(()));
+        assert_eq!(problem.grade(&wholes(&[6, 8])), Err(Mistake::NotReduced));
+        assert_eq!(problem.grade(&wholes(&[12, 16])), Err(Mistake::NotReduced));
+        assert_eq!(problem.grade(&wholes(&[4, 3])), Err(Mistake::NotEquivalent));
+        assert_eq!(problem.grade(&wholes(&[3, 0])), Err(Mistake::NotEquivalent));
+        assert_eq!(problem.grade(&wholes(&[3])), Err(Mistake::NotEquivalent));
+    }
+
+    #[test]
+    fn distractor_pairs_are_never_equivalent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for base in [Fraction::new(2, 3), Fraction::new(1, 1), Fraction::new(4, 4), Fraction::new(6, 2)] {
+            for multiplier in 2..=10 {
+                for _ in 0..50 {
+                    let equivalence = Equivalence::generate(&mut rng, base, multiplier);
+                    assert_eq!(equivalence.is_equivalent(), equivalence.distractor.is_none(), "{:?}", equivalence);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn equivalence_mistakes_name_the_misconception() {
+        let believed = Problem::Equivalent(Equivalence{a: Fraction::new(2, 3), b: Fraction::new(4, 5), distractor: Some(Misconception::AddedInsteadOfMultiplied)});
+        assert_eq!(believed.grade(&wholes(&[0])), Ok(()));
+        assert_eq!(believed.grade(&wholes(&[1])), Err(Mistake::Believed(Misconception::AddedInsteadOfMultiplied)));
+        let rejected = Problem::Equivalent(Equivalence{a: Fraction::new(2, 3), b: Fraction::new(4, 6), distractor: None});
+        assert_eq!(rejected.grade(&wholes(&[1])), Ok(()));
+        assert_eq!(rejected.grade(&wholes(&[0])), Err(Mistake::Rejected));
+    }
+
+    #[test]
+    fn chains_are_graded_field_by_field() {
+        let chain = Chain{fractions: [Fraction::new(2, 3), Fraction::new(4, 6), Fraction::new(8, 12), Fraction::default()], len: 3, hidden_numerators: [false, true, false, false]};
+        let problem = Problem::Chain(chain);
+        assert_eq!(problem.to_string(), "2/3 = ?/6 = 8/?");
+        assert_eq!(problem.solution(), wholes(&[4, 12]));
+        assert_eq!(problem.grade(&wholes(&[4, 12])), Ok(()));
+        assert_eq!(problem.grade_blanks(&wholes(&[4, 12])), [true, true]);
+        assert_eq!(problem.grade(&wholes(&[4, 9])), Err(Mistake::WrongTerm));
+        assert_eq!(problem.grade_blanks(&wholes(&[4, 9])), [true, false]);
+        assert_eq!(problem.grade_blanks(&wholes(&[6, 12])), [false, true]);
+        assert_eq!(problem.grade_blanks(&wholes(&[4])), [true, false]);
+    }
+
+    #[test]
+    fn comparisons_spot_terms_compared_alone() {
+        let comparison = Comparison{fractions: [Fraction::new(1, 3), Fraction::new(1, 4), Fraction::new(3, 4), Fraction::default(), Fraction::default()], len: 3};
+        let problem = Problem::Compare(comparison);
+        assert_eq!(problem.solution(), wholes(&[3, 1]));
+        assert_eq!(comparison.common_denominator(), 12);
+        assert_eq!(comparison.hint(), [(4, 4), (3, 3), (9, 3)]);
+        assert_eq!(problem.grade(&wholes(&[3, 1])), Ok(()));
+        assert_eq!(problem.grade(&wholes(&[2, 1])), Err(Mistake::ComparedNumerators));
+        assert_eq!(problem.grade(&wholes(&[1, 1])), Err(Mistake::ComparedDenominators));
+        // over the same denominator only the relation itself can be wrong
+        assert_eq!(problem.grade(&wholes(&[3, 3])), Err(Mistake::WrongRelation));
+        assert_eq!(problem.grade_blanks(&wholes(&[3, 3])), [true, false]);
+    }
+
+    #[test]
+    fn the_direction_of_the_hidden_term_names_the_mistake() {
+        for field in 0..4 {
+            let small_first = Problem::MissingTerm(Exercise{num_a: 2, den_a: 3, num_b: 8, den_b: 12, input_field_number: field, negative: [false; 4]});
+            let big_first = Problem::MissingTerm(Exercise{num_a: 8, den_a: 12, num_b: 2, den_b: 3, input_field_number: field, negative: [false; 4]});
+            let (in_a, in_b) = (Err(Mistake::CannotDivide), Err(Mistake::CannotMultiply));
+            assert_eq!(small_first.grade(&wholes(&[5])), if field < 2 {in_a} else {in_b});
+            assert_eq!(big_first.grade(&wholes(&[5])), if field < 2 {in_b} else {in_a});
+        }
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..100 {
+            assert!(Exercise::scaled_down(&mut rng, Ranges::default(), &Weakness::default(), 0.0).is_dividing());
+        }
+    }
+
+    #[test]
+    fn multipliers_may_be_fractions_and_inverted() {
+        let whole = Problem::Multiplier(Scaling{from: Fraction::new(2, 3), to: Fraction::new(8, 12)});
+        assert_eq!(whole.solution(), wholes(&[4]));
+        assert_eq!(whole.grade(&wholes(&[4])), Ok(()));
+        assert_eq!(whole.grade(&wholes(&[8, 2])), Ok(()));
+        assert_eq!(whole.grade(&wholes(&[1, 4])), Err(Mistake::InvertedRatio));
+        assert_eq!(whole.grade(&wholes(&[5])), Err(Mistake::WrongMultiplier));
+
+        let fractional = Problem::Multiplier(Scaling{from: Fraction::new(6, 9), to: Fraction::new(10, 15)});
+        assert_eq!(fractional.solution(), wholes(&[5, 3]));
+        assert_eq!(fractional.grade(&wholes(&[5, 3])), Ok(()));
+        assert_eq!(fractional.grade(&wholes(&[10, 6])), Ok(()));
+        assert_eq!(fractional.grade(&wholes(&[3, 5])), Err(Mistake::InvertedRatio));
+        assert_eq!(fractional.grade(&wholes(&[5, 0])), Err(Mistake::WrongMultiplier));
+        assert_eq!(fractional.grade(&wholes(&[2])), Err(Mistake::WrongMultiplier));
+    }
+
+    #[test]
+    fn mishandled_whole_parts_are_told_apart() {
+        let mixed = MixedNumber{whole: 2, fraction: Fraction::new(1, 3)};
+        let to_improper = Problem::MixedNumber(Conversion{mixed, multiplier: 2, to_improper: true});
+        assert_eq!(to_improper.to_string(), "2 1/3 = ?/6");
+        assert_eq!(to_improper.grade(&wholes(&[14])), Ok(()));
+        assert_eq!(to_improper.grade(&wholes(&[2])), Err(Mistake::WholePartSlip));
+        assert_eq!(to_improper.grade(&wholes(&[6])), Err(Mistake::WholePartSlip));
+        assert_eq!(to_improper.grade(&wholes(&[13])), Err(Mistake::WrongConversion));
+
+        let to_mixed = Problem::MixedNumber(Conversion{mixed, multiplier: 2, to_improper: false});
+        assert_eq!(to_mixed.to_string(), "14/6 = ? ?/3");
+        assert_eq!(to_mixed.grade(&wholes(&[2, 1])), Ok(()));
+        assert_eq!(to_mixed.grade(&wholes(&[2, 2])), Err(Mistake::WrongConversion));
+        assert_eq!(to_mixed.grade_blanks(&wholes(&[2, 2])), [true, false]);
+    }
+
+    #[test]
+    fn signs_keep_the_fractions_equivalent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let exercise = Exercise{num_a: 2, den_a: 3, num_b: 8, den_b: 12, input_field_number: 1, negative: [false; 4]};
+        for _ in 0..50 {
+            let signed = exercise.with_signs(&mut rng);
+            let term = |field| signed.signed_term(field);
+            assert_eq!(term(0) * term(3), term(2) * term(1), "{}", signed);
+        }
+        let problem = Problem::MissingTerm(Exercise{negative: [true, true, false, false], ..exercise});
+        assert_eq!(problem.to_string(), "-2/? = 8/12");
+        assert_eq!(problem.grade(&wholes(&[-3])), Ok(()));
+        assert_eq!(problem.grade(&wholes(&[3])), Err(Mistake::WrongSign));
+        assert_eq!(problem.grade(&wholes(&[-4])), Err(Mistake::CannotDivide));
+    }
+}
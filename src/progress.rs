@@ -0,0 +1,201 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::history::SessionRecord;
+use crate::weakness::Weakness;
+use crate::worksheet::escape_xml;
+
+const CHART_WIDTH: f32 = 640.0;
+const CHART_HEIGHT: f32 = 200.0;
+/// Room left of the plot for the values and under it for the dates.
+const AXIS_LEFT: f32 = 48.0;
+const AXIS_BOTTOM: f32 = 24.0;
+const CHART_MARGIN: f32 = 12.0;
+
+const STYLE: &str = "body{font-family:Helvetica,Arial,sans-serif;color:#222;max-width:720px;margin:24px auto;padding:0 16px}\
+h1{font-size:26px;margin-bottom:4px}h2{font-size:19px;margin-top:32px}.summary{color:#555}\
+table{border-collapse:collapse;font-size:13px}th,td{border:1px solid #ccc;padding:4px 6px;text-align:center;min-width:28px}\
+th{background:#eee}td.empty{background:#f6f6f6}li{margin:4px 0}";
+
+/// One finished session with answers, a point of the charts.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Point {
+    pub timestamp: u64,
+    pub problems: usize,
+    pub accuracy: f32,
+    /// Mean time spent on a problem.
+    pub seconds: f32,
+}
+
+/// The progress of a student over their sessions, written as a standalone HTML page with charts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub profile: String,
+    pub points: Vec<Point>,
+    pub weakness: Weakness,
+}
+impl Progress {
+    pub fn new<'a>(profile: &str, records: impl IntoIterator<Item = &'a SessionRecord>) -> Self {
+        let (mut points, mut weakness) = (Vec::new(), Weakness::default());
+        for record in records {
+            if record.attempts.is_empty() {
+                continue;
+            }
+            let count = record.attempts.len();
+            let correct = record.attempts.iter().filter(|attempt| attempt.correct).count();
+            let time: f32 = record.attempts.iter().map(|attempt| attempt.time.as_secs_f32()).sum();
+            points.push(Point{
+                timestamp: record.timestamp,
+                problems: count,
+                accuracy: correct as f32 / count as f32 * 100.0,
+                seconds: time / count as f32,
+            });
+            for attempt in &record.attempts {
+                weakness.record(attempt);
+            }
+        }
+        Progress{profile: String::from(profile), points, weakness}
+    }
+    pub fn html(&self) -> String {
+        let name = escape_xml(&self.profile);
+        let mut html = format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Progress of {0}</title>\n<style>{1}</style>\n</head>\n<body>\n<h1>Progress of {0}</h1>\n", name, STYLE);
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            html.push_str("<p class=\"summary\">No finished sessions yet.</p>\n</body>\n</html>\n");
+            return html;
+        };
+        let problems: usize = self.points.iter().map(|point| point.problems).sum();
+        let correct: f32 = self.points.iter().map(|point| point.accuracy * point.problems as f32 / 100.0).sum();
+        html.push_str(&format!("<p class=\"summary\">{} sessions from {} to {}, {} problems, {:.0}% correct</p>\n",
+                               self.points.len(), date(first.timestamp), date(last.timestamp), problems, correct / problems as f32 * 100.0));
+
+        html.push_str("<h2>Accuracy</h2>\n");
+        html.push_str(&self.chart(|point| point.accuracy, 100.0, "%"));
+        html.push_str("<h2>Time per problem</h2>\n");
+        let slowest = self.points.iter().map(|point| point.seconds).fold(0.0, f32::max);
+        html.push_str(&self.chart(|point| point.seconds, nice_ceiling(slowest), "s"));
+        html.push_str("<h2>Errors by times table and number</h2>\n");
+        html.push_str(&self.heatmap());
+
+        html.push_str("<h2>Suggested focus</h2>\n<ul>\n");
+        let advice = self.weakness.advice();
+        if advice.is_empty() {
+            html.push_str("<li>No repeated mistakes, keep practising</li>\n");
+        }
+        for line in advice {
+            html.push_str(&format!("<li>{}</li>\n", escape_xml(&line)));
+        }
+        html.push_str("</ul>\n</body>\n</html>\n");
+        html
+    }
+    /// A line chart of `value` for every session, from 0 to `max`.
+    fn chart(&self, value: impl Fn(&Point) -> f32, max: f32, unit: &str) -> String {
+        let (width, height) = (CHART_WIDTH - AXIS_LEFT - CHART_MARGIN, CHART_HEIGHT - AXIS_BOTTOM - CHART_MARGIN);
+        let x = |index: usize| match self.points.len() {
+            1 => {AXIS_LEFT + width / 2.0}
+            count => {AXIS_LEFT + width * index as f32 / (count - 1) as f32}
+        };
+        let y = |value: f32| CHART_MARGIN + height * (1.0 - value / max);
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-size=\"11\">\n", CHART_WIDTH, CHART_HEIGHT);
+        for step in 0..=4 {
+            let (value, line_y) = (max * step as f32 / 4.0, y(max * step as f32 / 4.0));
+            svg.push_str(&format!("<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"#ddd\"/>\n", AXIS_LEFT, line_y, CHART_WIDTH - CHART_MARGIN, line_y));
+            svg.push_str(&format!("<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}{}</text>\n", AXIS_LEFT - 6.0, line_y + 4.0, value, unit));
+        }
+        let points: Vec<String> = self.points.iter().enumerate().map(|(index, point)| format!("{:.1},{:.1}", x(index), y(value(point)))).collect();
+        svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"#2f6fbf\" stroke-width=\"2\"/>\n", points.join(" ")));
+        for (index, point) in self.points.iter().enumerate() {
+            svg.push_str(&format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3.5\" fill=\"#2f6fbf\"><title>{}: {:.1}{}</title></circle>\n",
+                                  x(index), y(value(point)), date(point.timestamp), value(point), unit));
+        }
+        let last = self.points.len() - 1;
+        for (index, anchor) in if last == 0 {vec![(0, "middle")]} else {vec![(0, "start"), (last, "end")]} {
+            svg.push_str(&format!("<text x=\"{:.1}\" y=\"{}\" text-anchor=\"{}\">{}</text>\n", x(index), CHART_HEIGHT - 6.0, anchor, date(self.points[index].timestamp)));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+    /// The multipliers down and the base numbers across, each cell coloured from green to red by its error rate.
+    fn heatmap(&self) -> String {
+        let pairs = &self.weakness.pairs;
+        let multipliers: Vec<u32> = (1..=pairs.rows.len() as u32).filter(|multiplier| pairs.row(*multiplier).is_some_and(|row| row.seen.iter().any(|seen| *seen > 0))).collect();
+        let numbers: Vec<u32> = (1..=pairs.rows.iter().map(|row| row.seen.len()).max().unwrap_or(0) as u32)
+            .filter(|number| pairs.rows.iter().any(|row| row.seen(*number) > 0))
+            .collect();
+        if multipliers.is_empty() {
+            return String::from("<p>No missing-term exercises answered yet.</p>\n");
+        }
+        let mut table = String::from("<table>\n<tr><th>×</th>");
+        for number in &numbers {
+            table.push_str(&format!("<th>{}</th>", number));
+        }
+        table.push_str("</tr>\n");
+        for multiplier in multipliers {
+            table.push_str(&format!("<tr><th>{}</th>", multiplier));
+            for number in &numbers {
+                let row = pairs.row(multiplier);
+                match row.and_then(|row| row.error_rate(*number)) {
+                    Some(rate) => {
+                        let (errors, seen) = row.map(|row| (row.errors(*number), row.seen(*number))).unwrap_or_default();
+                        table.push_str(&format!("<td style=\"background:hsl({:.0},70%,72%)\" title=\"{} × {}: {} of {} wrong\">{}/{}</td>",
+                                                120.0 * (1.0 - rate), multiplier, number, errors, seen, errors, seen));
+                    }
+                    None => {table.push_str("<td class=\"empty\"></td>")}
+                }
+            }
+            table.push_str("</tr>\n");
+        }
+        table.push_str("</table>\n");
+        table
+    }
+    pub fn export(&self, path: &Path) -> io::Result<PathBuf> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.html())?;
+        Ok(path.to_path_buf())
+    }
+}
+
+/// The smallest of 1, 2 or 5 times a power of ten at least `value`, so the axis shows round numbers.
+fn nice_ceiling(value: f32) -> f32 {
+    let power = 10f32.powf(value.max(1.0).log10().floor());
+    [1.0, 2.0, 5.0, 10.0].iter().map(|step| step * power).find(|ceiling| *ceiling >= value).unwrap_or(10.0 * power)
+}
+
+/// `timestamp`, in seconds since the Unix epoch, as a `YYYY-MM-DD` date in UTC.
+fn date(timestamp: u64) -> String {
+    // days since 0000-03-01, counted in eras of 400 years so leap years repeat
+    let days = timestamp / 86400 + 719468;
+    let (era, day_of_era) = (days / 146097, days % 146097);
+    let year_of_era = (day_of_era - day_of_era/1460 + day_of_era/36524 - day_of_era/146096) / 365;
+    let day_of_year = day_of_era - (365*year_of_era + year_of_era/4 - year_of_era/100);
+    let month_index = (5*day_of_year + 2) / 153;
+    let day = day_of_year - (153*month_index + 2)/5 + 1;
+    let month = if month_index < 10 {month_index + 3} else {month_index - 9};
+    let year = era*400 + year_of_era + (month <= 2) as u64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_match_the_calendar() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(951_868_799), "2000-02-29");
+        assert_eq!(date(951_868_800), "2000-03-01");
+        assert_eq!(date(1_709_164_800), "2024-02-29");
+        assert_eq!(date(1_735_689_599), "2024-12-31");
+        assert_eq!(date(4_107_542_400), "2100-03-01");
+    }
+
+    #[test]
+    fn axes_end_on_round_numbers() {
+        assert_eq!(nice_ceiling(0.4), 1.0);
+        assert_eq!(nice_ceiling(13.0), 20.0);
+        assert_eq!(nice_ceiling(50.0), 50.0);
+        assert_eq!(nice_ceiling(51.0), 100.0);
+    }
+}
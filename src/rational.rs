@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Digits accepted in whole numbers, numerators and denominators, enough for any answer and far from overflowing.
+const MAX_DIGITS: usize = 9;
+
+/// Decimal places accepted and written, two more than `MAX_DIGITS` so a percentage with all its decimals
+/// is written back as a decimal that parses again.
+const MAX_PLACES: usize = MAX_DIGITS + 2;
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+/// An exact number, always kept in lowest terms with a positive denominator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i64,
+    den: i64,
+}
+impl Default for Rational {
+    fn default() -> Self {
+        Rational::ZERO
+    }
+}
+impl Rational {
+    pub const ZERO: Rational = Rational{num: 0, den: 1};
+
+    /// `num/den` in lowest terms, zero when `den` is 0.
+    pub fn new(num: i64, den: i64) -> Self {
+        Rational::reduced(num as i128, den as i128).unwrap_or(Rational::ZERO)
+    }
+    /// `num/den` in lowest terms, `None` when `den` is 0 or the terms do not fit.
+    fn reduced(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let divisor = gcd(num, den) * den.signum();
+        Some(Rational{num: i64::try_from(num/divisor).ok()?, den: i64::try_from(den/divisor).ok()?})
+    }
+    pub const fn whole(value: i64) -> Self {
+        Rational{num: value, den: 1}
+    }
+    pub fn num(&self) -> i64 {
+        self.num
+    }
+    pub fn den(&self) -> i64 {
+        self.den
+    }
+    pub fn abs(&self) -> Self {
+        Rational{num: self.num.abs(), den: self.den}
+    }
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+    /// The value as a positive whole number, `None` for anything else.
+    pub fn to_u32(&self) -> Option<u32> {
+        if self.den == 1 && self.num > 0 {u32::try_from(self.num).ok()} else {None}
+    }
+    pub fn scaled(&self, factor: i64) -> Self {
+        Rational::reduced(self.num as i128 * factor as i128, self.den as i128).unwrap_or(Rational::ZERO)
+    }
+    pub fn divided(&self, divisor: i64) -> Self {
+        Rational::reduced(self.num as i128, self.den as i128 * divisor as i128).unwrap_or(Rational::ZERO)
+    }
+}
+impl From<u32> for Rational {
+    fn from(value: u32) -> Self {
+        Rational::whole(value as i64)
+    }
+}
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Whole numbers as they are, decimals when the denominator allows it (`0.75`) and `num/den` otherwise.
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            return write!(f, "{}", self.num);
+        }
+        // a finite decimal needs a denominator made of 2s and 5s only
+        let (mut rest, mut twos, mut fives) = (self.den, 0, 0);
+        while rest % 2 == 0 {
+            rest /= 2;
+            twos += 1;
+        }
+        while rest % 5 == 0 {
+            rest /= 5;
+            fives += 1;
+        }
+        let places: usize = twos.max(fives);
+        if rest != 1 || places > MAX_PLACES {
+            return write!(f, "{}/{}", self.num, self.den);
+        }
+        let scaled = self.num.unsigned_abs() as i128 * 10_i128.pow(places as u32) / self.den as i128;
+        let digits = format!("{:0>width$}", scaled, width = places + 1);
+        let (whole, decimals) = digits.split_at(digits.len() - places);
+        let sign = if self.num < 0 {"-"} else {""};
+        write!(f, "{}{}.{}", sign, whole, decimals.trim_end_matches('0'))
+    }
+}
+/// Parses whole numbers, decimals with a point or a comma, percentages and `num/den`, with an optional leading minus.
+impl FromStr for Rational {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not a number", s);
+        let trimmed = s.trim();
+        let (unsigned, negative) = match trimmed.strip_prefix('-') {
+            Some(unsigned) => {(unsigned.trim_start(), true)}
+            None => {(trimmed, false)}
+        };
+        let (digits, percent) = match unsigned.strip_suffix('%') {
+            Some(digits) => {(digits.trim_end(), true)}
+            None => {(unsigned, false)}
+        };
+        let (num, den) = if let Some((num, den)) = digits.split_once('/') {
+            // a percentage of a fraction is nothing anyone types, and could not be written back
+            if percent {
+                return Err(invalid());
+            }
+            (whole_digits(num.trim(), MAX_DIGITS).ok_or_else(invalid)?, whole_digits(den.trim(), MAX_DIGITS).ok_or_else(invalid)?)
+        } else {
+            let (whole, decimals) = digits.split_once(['.', ',']).unwrap_or((digits, ""));
+            if whole.is_empty() && decimals.is_empty() {
+                return Err(invalid());
+            }
+            let whole = if whole.is_empty() {0} else {whole_digits(whole, MAX_DIGITS).ok_or_else(invalid)?};
+            let places = if percent {MAX_DIGITS} else {MAX_PLACES};
+            let decimals_value = if decimals.is_empty() {0} else {whole_digits(decimals, places).ok_or_else(invalid)?};
+            let unit = 10_i128.pow(decimals.len() as u32);
+            (whole * unit + decimals_value, unit)
+        };
+        let den = if percent {den * 100} else {den};
+        Rational::reduced(if negative {-num} else {num}, den).ok_or_else(invalid)
+    }
+}
+
+fn whole_digits(digits: &str, max_digits: usize) -> Option<i128> {
+    if digits.is_empty() || digits.len() > max_digits || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Whole numbers are stored as JSON numbers, like the answers of older history files, the others as text.
+impl Serialize for Rational {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.den == 1 {
+            serializer.serialize_i64(self.num)
+        } else {
+            serializer.serialize_str(&self.to_string())
+        }
+    }
+}
+impl<'de> Deserialize<'de> for Rational {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Whole(i64),
+            Text(String),
+        }
+        match Stored::deserialize(deserializer)? {
+            Stored::Whole(value) => {Ok(Rational::whole(value))}
+            Stored::Text(text) => {text.parse().map_err(serde::de::Error::custom)}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Rational {
+        text.parse().unwrap()
+    }
+
+    /// Writes `value` to JSON and reads it back.
+    fn round_trip(value: Rational) -> Rational {
+        serde_json::from_str(&serde_json::to_string(&value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_decimals_percentages_and_fractions() {
+        assert_eq!(parse("0,5"), Rational::new(1, 2));
+        assert_eq!(parse("0.5"), Rational::new(1, 2));
+        assert_eq!(parse(".5"), Rational::new(1, 2));
+        assert_eq!(parse("75%"), Rational::new(3, 4));
+        assert_eq!(parse("-3/4"), Rational::new(-3, 4));
+        assert_eq!(parse("6/8"), Rational::new(3, 4));
+        assert_eq!(parse("12"), Rational::whole(12));
+        assert_eq!(parse("12.123456789"), Rational::new(12_123_456_789, 1_000_000_000));
+    }
+
+    #[test]
+    fn rejects_malformed_numbers() {
+        for text in ["", "-", ".", "1/0", "a", "1.2.3", "1/2%", "1234567890", "0.123456789012", "0.1234567891%", "999999999.99999999999"] {
+            assert!(text.parse::<Rational>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn displays_decimals_when_finite() {
+        assert_eq!(Rational::whole(7).to_string(), "7");
+        assert_eq!(Rational::new(3, 4).to_string(), "0.75");
+        assert_eq!(Rational::new(-3, 4).to_string(), "-0.75");
+        assert_eq!(Rational::new(1, 3).to_string(), "1/3");
+        assert_eq!(parse("12.123456789").to_string(), "12.123456789");
+        assert_eq!(parse("9999999.99999999999").to_string(), "9999999.99999999999");
+        assert_eq!(parse("0.123456789%").to_string(), "0.00123456789");
+    }
+
+    #[test]
+    fn round_trips_through_text_and_json() {
+        for text in ["0,5", "75%", "-3/4", "1/3", "12", "-12.5", "12.123456789", "9999999.99999999999", "999999999.999999999%", "-0.00000000001"] {
+            let value = parse(text);
+            assert_eq!(parse(&value.to_string()), value, "{}", text);
+            assert_eq!(round_trip(value), value, "{}", text);
+        }
+    }
+
+    #[test]
+    fn whole_numbers_are_stored_as_json_numbers() {
+        assert_eq!(serde_json::to_string(&Rational::whole(12)).unwrap(), "12");
+        assert_eq!(serde_json::to_string(&Rational::new(3, 4)).unwrap(), "\"0.75\"");
+        assert_eq!(serde_json::from_str::<Rational>("12").unwrap(), Rational::whole(12));
+    }
+}
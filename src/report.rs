@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::engine::Ending;
+use crate::history::SessionRecord;
+use crate::weakness::Weakness;
+
+/// One answered problem of a report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProblemRow {
+    /// Number of the session in the report, from 1.
+    pub session: usize,
+    pub number: usize,
+    pub kind: String,
+    pub problem: String,
+    pub answers: String,
+    pub solution: String,
+    pub correct: bool,
+    pub mistake: Option<String>,
+    pub seconds: f64,
+    pub hinted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionRow {
+    pub session: usize,
+    pub timestamp: u64,
+    pub seed: u64,
+    pub kind: String,
+    pub problems: usize,
+    pub correct: usize,
+    pub errors: usize,
+    pub accuracy: f32,
+    pub seconds: f64,
+    pub ending: Ending,
+}
+
+/// How often a times table came up and was missed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableRow {
+    pub multiplier: u32,
+    pub seen: u32,
+    pub errors: u32,
+}
+
+/// Every problem of some sessions of a profile, with the error tally of the times tables,
+/// written as JSON or as CSV for spreadsheets.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub profile: String,
+    pub sessions: Vec<SessionRow>,
+    pub problems: Vec<ProblemRow>,
+    pub tables: Vec<TableRow>,
+    /// How many times each kind of mistake was made.
+    pub mistakes: BTreeMap<String, u32>,
+}
+impl Report {
+    pub fn new<'a>(profile: &str, records: impl IntoIterator<Item = &'a SessionRecord>) -> Self {
+        let (mut sessions, mut problems, mut weakness) = (Vec::new(), Vec::new(), Weakness::default());
+        for (index, record) in records.into_iter().enumerate() {
+            let correct = record.attempts.iter().filter(|attempt| attempt.correct).count();
+            let count = record.attempts.len();
+            sessions.push(SessionRow{
+                session: index + 1,
+                timestamp: record.timestamp,
+                seed: record.seed,
+                kind: record.settings.kind.to_string(),
+                problems: count,
+                correct,
+                errors: count - correct,
+                accuracy: if count == 0 {0.0} else {correct as f32 / count as f32 * 100.0},
+                seconds: record.elapsed.as_secs_f64(),
+                ending: record.ending,
+            });
+            for (number, attempt) in record.attempts.iter().enumerate() {
+                let problem = attempt.problem;
+                problems.push(ProblemRow{
+                    session: index + 1,
+                    number: number + 1,
+                    kind: problem.kind().to_string(),
+                    problem: problem.to_string(),
+                    answers: problem.format_answers(&attempt.answers),
+                    solution: problem.format_answers(&problem.solution()),
+                    correct: attempt.correct,
+                    mistake: problem.grade(&attempt.answers).err().map(|mistake| mistake.to_string()),
+                    seconds: attempt.time.as_secs_f64(),
+                    hinted: attempt.hinted,
+                });
+                weakness.record(attempt);
+            }
+        }
+        let tally = &weakness.multipliers;
+        let tables = (1..=tally.seen.len() as u32)
+            .filter(|multiplier| tally.seen(*multiplier) > 0)
+            .map(|multiplier| TableRow{multiplier, seen: tally.seen(multiplier), errors: tally.errors(multiplier)})
+            .collect();
+        let mistakes = weakness.mistakes.iter().map(|(mistake, count)| (mistake.to_string(), *count)).collect();
+        Report{profile: String::from(profile), sessions, problems, tables, mistakes}
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+    /// One line per problem.
+    pub fn problems_csv(&self) -> String {
+        let mut csv = String::from("session,number,kind,problem,answers,solution,correct,mistake,seconds,hinted\n");
+        for row in &self.problems {
+            csv.push_str(&csv_line(&[
+                row.session.to_string(),
+                row.number.to_string(),
+                row.kind.clone(),
+                row.problem.clone(),
+                row.answers.clone(),
+                row.solution.clone(),
+                row.correct.to_string(),
+                row.mistake.clone().unwrap_or_default(),
+                format!("{:.1}", row.seconds),
+                row.hinted.to_string(),
+            ]));
+        }
+        csv
+    }
+    /// One line per session.
+    pub fn sessions_csv(&self) -> String {
+        let mut csv = String::from("session,timestamp,seed,kind,problems,correct,errors,accuracy,seconds,ending\n");
+        for row in &self.sessions {
+            csv.push_str(&csv_line(&[
+                row.session.to_string(),
+                row.timestamp.to_string(),
+                row.seed.to_string(),
+                row.kind.clone(),
+                row.problems.to_string(),
+                row.correct.to_string(),
+                row.errors.to_string(),
+                format!("{:.1}", row.accuracy),
+                format!("{:.1}", row.seconds),
+                format!("{:?}", row.ending),
+            ]));
+        }
+        csv
+    }
+    /// One line per times table that came up.
+    pub fn tables_csv(&self) -> String {
+        let mut csv = String::from("multiplier,seen,errors,error rate\n");
+        for row in &self.tables {
+            csv.push_str(&csv_line(&[
+                row.multiplier.to_string(),
+                row.seen.to_string(),
+                row.errors.to_string(),
+                format!("{:.2}", row.errors as f32 / row.seen as f32),
+            ]));
+        }
+        csv
+    }
+    /// Writes the report as the extension of `path` asks: one `.json` file, or a `.csv` of the problems
+    /// with `-sessions.csv` and `-tables.csv` files next to it; returns the files written.
+    pub fn export(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match path.extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_ascii_lowercase()).as_deref() {
+            Some("json") => {
+                fs::write(path, self.to_json())?;
+                Ok(vec![path.to_path_buf()])
+            }
+            Some("csv") => {
+                let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("report");
+                let sessions = path.with_file_name(format!("{}-sessions.csv", stem));
+                let tables = path.with_file_name(format!("{}-tables.csv", stem));
+                fs::write(path, self.problems_csv())?;
+                fs::write(&sessions, self.sessions_csv())?;
+                fs::write(&tables, self.tables_csv())?;
+                Ok(vec![path.to_path_buf(), sessions, tables])
+            }
+            _ => {Err(io::Error::new(io::ErrorKind::InvalidInput, "the report file must end in .csv or .json"))}
+        }
+    }
+}
+
+/// `profile` made safe to use in a file name: anything but letters, digits, `-` and `_` becomes `_`,
+/// so that no name can reach outside the folder the file is written to.
+pub fn file_name(profile: &str) -> String {
+    let name: String = profile.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' {c} else {'_'}).collect();
+    if name.is_empty() {String::from("_")} else {name}
+}
+
+/// `fields` separated by commas, quoted when they hold a comma, a quote or a line break.
+fn csv_line(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    fields.join(",") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_names_stay_in_their_folder() {
+        assert_eq!(file_name("Anna-Lena_2"), "Anna-Lena_2");
+        assert_eq!(file_name("Zoë"), "Zoë");
+        assert_eq!(file_name("../../etc/passwd"), "______etc_passwd");
+        assert_eq!(file_name("a\\b c.d"), "a_b_c_d");
+        assert_eq!(file_name(""), "_");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        let fields = ["2/3".to_string(), "8/12, 3".to_string(), "say \"yes\"".to_string(), "two\nlines".to_string(), String::new()];
+        assert_eq!(csv_line(&fields), "2/3,\"8/12, 3\",\"say \"\"yes\"\"\",\"two\nlines\",\n");
+    }
+}
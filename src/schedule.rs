@@ -0,0 +1,125 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::engine::{Attempt, Exercise};
+use crate::problem::Problem;
+
+const DAY: u64 = 24*60*60;
+
+/// Days to wait before reviewing a fact again, by Leitner box; a wrong answer sends it back to box 0.
+pub const INTERVALS: [u64; 6] = [0, 1, 2, 4, 8, 16];
+
+/// A base fraction and the multiplier scaling it, whatever term was hidden.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fact {
+    pub num: u32,
+    pub den: u32,
+    pub multiplier: u32,
+}
+impl Fact {
+    /// The fact behind a missing-term problem, the other kinds are not scheduled.
+    pub fn of(problem: &Problem) -> Option<Self> {
+        match problem {
+            Problem::MissingTerm(exercise) => {Some(Fact::of_exercise(exercise))}
+            _ => {None}
+        }
+    }
+    pub fn of_exercise(exercise: &Exercise) -> Self {
+        if exercise.num_a < exercise.num_b {
+            Fact{num: exercise.num_a, den: exercise.den_a, multiplier: exercise.num_b/exercise.num_a}
+        } else {
+            Fact{num: exercise.num_b, den: exercise.den_b, multiplier: exercise.num_a/exercise.num_b}
+        }
+    }
+    pub fn exercise<R: Rng>(&self, rng: &mut R) -> Exercise {
+        Exercise::scaled(rng, self.num, self.den, self.multiplier)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub fact: Fact,
+    /// Leitner box, an index into `INTERVALS`.
+    pub level: usize,
+    /// Seconds since the Unix epoch from which the fact should be reviewed.
+    pub due: u64,
+}
+
+/// The review schedule of the facts a student has met.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub cards: Vec<Card>,
+}
+impl Schedule {
+    /// Moves the fact up a box when answered right and back to the first one otherwise.
+    pub fn review(&mut self, fact: Fact, correct: bool, now: u64) {
+        let index = match self.cards.iter().position(|card| card.fact == fact) {
+            Some(index) => {index}
+            None => {
+                self.cards.push(Card{fact, level: 0, due: now});
+                self.cards.len() - 1
+            }
+        };
+        let card = &mut self.cards[index];
+        card.level = if correct {(card.level + 1).min(INTERVALS.len() - 1)} else {0};
+        card.due = now + INTERVALS[card.level]*DAY;
+    }
+    pub fn review_attempts<'a>(&mut self, attempts: impl IntoIterator<Item = &'a Attempt>, now: u64) {
+        for attempt in attempts {
+            if let Some(fact) = Fact::of(&attempt.problem) {
+                self.review(fact, attempt.correct, now);
+            }
+        }
+    }
+    /// Facts due at `now`, the most overdue first.
+    pub fn due(&self, now: u64) -> Vec<Fact> {
+        let mut due: Vec<&Card> = self.cards.iter().filter(|card| card.due <= now).collect();
+        due.sort_by_key(|card| (card.due, card.level));
+        due.into_iter().map(|card| card.fact).collect()
+    }
+    pub fn due_count(&self, now: u64) -> usize {
+        self.cards.iter().filter(|card| card.due <= now).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: Fact = Fact{num: 1, den: 2, multiplier: 3};
+    const THIRD: Fact = Fact{num: 1, den: 3, multiplier: 4};
+
+    #[test]
+    fn right_answers_move_a_fact_up_a_box() {
+        let mut schedule = Schedule::default();
+        // past the last box it stays there
+        for reviews in 1..=INTERVALS.len() + 1 {
+            schedule.review(HALF, true, 0);
+            let level = reviews.min(INTERVALS.len() - 1);
+            assert_eq!(schedule.cards, [Card{fact: HALF, level, due: INTERVALS[level]*DAY}]);
+        }
+    }
+
+    #[test]
+    fn a_wrong_answer_sends_a_fact_back_to_the_first_box() {
+        let mut schedule = Schedule::default();
+        schedule.review(HALF, true, 0);
+        schedule.review(HALF, true, 0);
+        schedule.review(HALF, false, 5*DAY);
+        assert_eq!(schedule.cards, [Card{fact: HALF, level: 0, due: 5*DAY}]);
+        assert_eq!(schedule.due(5*DAY), [HALF]);
+    }
+
+    #[test]
+    fn the_most_overdue_facts_come_first() {
+        let fifth = Fact{num: 2, den: 5, multiplier: 2};
+        let schedule = Schedule{cards: vec![
+            Card{fact: HALF, level: 2, due: 3*DAY},
+            Card{fact: THIRD, level: 1, due: DAY},
+            Card{fact: fifth, level: 0, due: 3*DAY},
+            Card{fact: Fact{num: 3, den: 4, multiplier: 5}, level: 4, due: 9*DAY},
+        ]};
+        assert_eq!(schedule.due(4*DAY), [THIRD, fifth, HALF]);
+        assert_eq!(schedule.due_count(4*DAY), 3);
+        assert!(schedule.due(0).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+use propinva::engine::{unix_time, Session, Settings};
+use propinva::history::{History, HistoryError, SessionRecord, DEFAULT_PROFILE};
+use propinva::progress::Progress;
+use propinva::report::Report;
+use propinva::weakness::Weakness;
+use crate::cli::Cli;
+
+/// The history the finished sessions are recorded in and the profile practising, for the window
+/// and the terminal front-ends alike.
+#[derive(Debug, Clone, Default)]
+pub struct Store {
+    pub history: History,
+    /// Where the history is saved, `None` when it is not.
+    pub path: Option<PathBuf>,
+    /// The profile picked on the command line, last time or since; sessions go to `DEFAULT_PROFILE` until one is.
+    pub profile: Option<String>,
+    /// Why the history is not saved, or what went wrong with it last.
+    pub error: Option<String>,
+}
+impl Store {
+    /// The history `cli` points to, and the settings of the profile unless options on the command line
+    /// override them.
+    pub fn open(cli: &Cli) -> (Self, Settings) {
+        let mut store = Store::default();
+        match cli.history_path() {
+            Some(path) => {
+                match History::load(&path) {
+                    Ok(history) => {
+                        store.history = history;
+                        store.path = Some(path);
+                    }
+                    // never overwrite a history we could not read
+                    Err(error) => {store.error = Some(error.to_string())}
+                }
+            }
+            None => {store.error = Some(String::from("no data directory to save the history"))}
+        }
+        store.profile = cli.profile.clone().or(store.history.selected.clone());
+        let settings = match store.profile.as_ref().and_then(|name| store.history.profile(name)) {
+            Some(saved) if !cli.has_session_options() => {Settings{seed: cli.seed, ..saved.settings}}
+            _ => {cli.settings()}
+        };
+        (store, settings)
+    }
+    /// The profile practising.
+    pub fn name(&self) -> String {
+        self.profile.clone().unwrap_or_else(|| String::from(DEFAULT_PROFILE))
+    }
+    pub fn save(&mut self) -> Result<(), HistoryError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let saved = self.history.save(path);
+        self.error = saved.as_ref().err().map(|error| error.to_string());
+        saved
+    }
+    /// A session of `settings` knowing the weaknesses and the due facts of the earlier sessions of the profile.
+    pub fn session(&self, settings: Settings) -> Session {
+        let mut session = Session::new(settings);
+        if let Some(profile) = self.history.profile(&self.name()) {
+            session.set_prior_weakness(Weakness::from_attempts(profile.attempts()));
+            session.set_due_facts(profile.schedule.due(unix_time()));
+        }
+        session
+    }
+    /// The stored sessions of profile `name`, only the `last` ones if given.
+    fn sessions(&self, name: &str, last: Option<usize>) -> Result<&[SessionRecord], HistoryError> {
+        let profile = self.history.profile(name).ok_or(HistoryError::UnknownProfile(String::from(name)))?;
+        let skipped = profile.sessions.len().saturating_sub(last.unwrap_or(usize::MAX));
+        Ok(&profile.sessions[skipped..])
+    }
+    pub fn report(&self, last: Option<usize>) -> Result<Report, HistoryError> {
+        let name = self.name();
+        Ok(Report::new(&name, self.sessions(&name, last)?))
+    }
+    pub fn progress(&self, name: &str, last: Option<usize>) -> Result<Progress, HistoryError> {
+        Ok(Progress::new(name, self.sessions(name, last)?))
+    }
+    /// Records a finished session and makes its settings the profile's, unless nothing was answered.
+    pub fn record(&mut self, session: &Session) -> Result<(), HistoryError> {
+        if session.attempts().is_empty() {
+            return Ok(());
+        }
+        let name = self.name();
+        let profile = self.history.profile_or_insert(&name);
+        profile.settings = Settings{seed: None, ..session.settings()};
+        profile.add_session(SessionRecord::from(session));
+        self.history.selected = Some(name.clone());
+        self.profile = Some(name);
+        self.save()
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::new_ret_no_self)]
+
 use iced::{Border, Color};
 use iced::border::Radius;
 use iced::widget::button;
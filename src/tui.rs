@@ -0,0 +1,232 @@
+use std::io::{self, BufRead, Write};
+use propinva::engine::{parse_answer, Session, SessionLength, Settings};
+use propinva::history::HistoryError;
+use propinva::layout::{cells, Cell, Term};
+use propinva::problem::{relation, Mistake, Problem};
+use propinva::rational::Rational;
+use crate::cli::Cli;
+use crate::store::Store;
+
+/// Blank `index` as it is named in the prompts: `[a]`, `[b]`...
+fn label(index: usize) -> String {
+    format!("[{}]", (b'a' + index as u8) as char)
+}
+
+/// Draws `cells` side by side on three rows, fractions with their terms centred over and under a line,
+/// and the blanks named or, when `solved`, filled with their solution.
+fn draw(cells: &[Cell], solved: bool) -> String {
+    let term = |term: &Term| match term {
+        Term::Given(text) => {text.clone()}
+        Term::Blank{solution, ..} if solved => {solution.clone()}
+        Term::Blank{index, ..} => {label(*index)}
+    };
+    let mut rows = [String::new(), String::new(), String::new()];
+    for cell in cells {
+        let (top, middle, bottom) = match cell {
+            Cell::Fraction(numerator, denominator) => {
+                let (numerator, denominator) = (term(numerator), term(denominator));
+                let width = numerator.chars().count().max(denominator.chars().count()) + 2;
+                (format!("{:^width$}", numerator), "─".repeat(width), format!("{:^width$}", denominator))
+            }
+            Cell::Text(text) => {
+                let text = term(text);
+                let width = text.chars().count();
+                (" ".repeat(width), text, " ".repeat(width))
+            }
+        };
+        for (row, part) in rows.iter_mut().zip([top, middle, bottom]) {
+            row.push_str(&part);
+            row.push(' ');
+        }
+    }
+    rows.map(|row| String::from(row.trim_end())).join("\n")
+}
+
+/// The sessions of the window played in a terminal, one line of input per answer; the end of the input
+/// finishes the session as if it was typed `q`.
+struct Trainer<R, W> {
+    input: R,
+    output: W,
+    settings: Settings,
+    store: Store,
+}
+impl<R: BufRead, W: Write> Trainer<R, W> {
+    /// The next line, trimmed, `None` at the end of the input or on `q`.
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        write!(self.output, "{}", prompt)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            return Ok(None);
+        }
+        let line = line.trim();
+        Ok(if line.eq_ignore_ascii_case("q") {None} else {Some(String::from(line))})
+    }
+    fn report(&mut self, saved: Result<(), HistoryError>) -> io::Result<()> {
+        match saved {
+            Ok(()) => {Ok(())}
+            Err(error) => {writeln!(self.output, "Not saved: {}", error)}
+        }
+    }
+    fn run(&mut self) -> io::Result<()> {
+        let ranges = self.settings.ranges();
+        writeln!(self.output, "Exercises on Invariant Property")?;
+        writeln!(self.output, "{}: {}, {}, multipliers {}, numbers {}", self.store.name(), self.settings.kind, self.settings.length, ranges.multiplier, ranges.number)?;
+        writeln!(self.output, "Answer each blank and press Enter, q to finish.")?;
+        if let Some(error) = &self.store.error {
+            writeln!(self.output, "Not saved: {}", error)?;
+        }
+
+        let mut session = self.store.session(self.settings);
+        session.start();
+        loop {
+            self.exercise(&mut session)?;
+            let summary = session.finish();
+            let saved = self.store.record(&session);
+            self.report(saved)?;
+
+            writeln!(self.output, "\n{}", summary.headline())?;
+            writeln!(self.output, "Correct: {}  Errors: {}  Accuracy: {}%", summary.correct, summary.errors, summary.accuracy)?;
+            if let Some(breakdown) = session.weakness().breakdown() {
+                writeln!(self.output, "{}", breakdown)?;
+            }
+            for advice in session.weakness().advice() {
+                writeln!(self.output, "{}", advice)?;
+            }
+            writeln!(self.output, "Seed: {}", session.seed())?;
+            let Some(retry) = session.retry_mistakes() else {
+                return Ok(());
+            };
+            match self.read_line("\nr to practise the mistakes, Enter to quit: ")? {
+                Some(choice) if choice.eq_ignore_ascii_case("r") => {
+                    session = retry;
+                    session.start();
+                }
+                _ => {return Ok(())}
+            }
+        }
+    }
+    /// Asks problems until the session is over or the student stops.
+    fn exercise(&mut self, session: &mut Session) -> io::Result<()> {
+        loop {
+            let problem = session.problem();
+            let count = match (session.remaining_mistakes(), session.settings().length) {
+                (Some(remaining), _) => {format!("{} mistakes left", remaining)}
+                (None, SessionLength::Count(count)) => {format!("{}/{}", session.exercise_count() + 1, count)}
+                (None, _) => {format!("{}", session.exercise_count() + 1)}
+            };
+            writeln!(self.output, "\n{}  {}", count, problem.kind())?;
+            writeln!(self.output, "{}", draw(&cells(&problem), false))?;
+            let Some(answers) = self.answers(session)? else {
+                return Ok(());
+            };
+            if session.answer(answers.clone()) {
+                writeln!(self.output, "Correct")?;
+            } else {
+                let verdict = format!("Inserted {}, the answer is {}", problem.format_answers(&answers), problem.format_answers(&problem.solution()));
+                match problem.grade(&answers) {
+                    Err(mistake) if mistake != Mistake::WrongTerm => {writeln!(self.output, "{} ({})", verdict, mistake)?}
+                    _ => {writeln!(self.output, "{}", verdict)?}
+                }
+                writeln!(self.output, "{}", draw(&cells(&problem), true))?;
+            }
+            if session.is_over() {
+                return Ok(());
+            }
+            session.next();
+        }
+    }
+    /// Reads the answers to the problem on screen, `None` when the student stops.
+    fn answers(&mut self, session: &mut Session) -> io::Result<Option<Vec<Rational>>> {
+        let problem = session.problem();
+        if let Problem::Equivalent(_) = problem {
+            loop {
+                match self.read_line("Equivalent? (y/n) ")? {
+                    None => {return Ok(None)}
+                    Some(choice) if choice.eq_ignore_ascii_case("y") => {return Ok(Some(vec![Rational::whole(1)]))}
+                    Some(choice) if choice.eq_ignore_ascii_case("n") => {return Ok(Some(vec![Rational::ZERO]))}
+                    Some(_) => {}
+                }
+            }
+        }
+        let mut answers = Vec::new();
+        for blank in 0..problem.blanks() {
+            let answer = loop {
+                let Some(line) = self.read_line(&format!("{} ", label(blank)))? else {
+                    return Ok(None);
+                };
+                if let Problem::Compare(comparison) = problem {
+                    match line.as_str() {
+                        "<" => {break Rational::from(relation(std::cmp::Ordering::Less))}
+                        "=" => {break Rational::from(relation(std::cmp::Ordering::Equal))}
+                        ">" => {break Rational::from(relation(std::cmp::Ordering::Greater))}
+                        "?" => {
+                            session.use_hint();
+                            let common = comparison.common_denominator();
+                            let hint: Vec<String> = comparison.hint().iter().map(|(numerator, _)| format!("{}/{}", numerator, common)).collect();
+                            writeln!(self.output, "{}", hint.join("  "))?;
+                        }
+                        _ => {writeln!(self.output, "Type <, = or >, ? for a hint")?}
+                    }
+                    continue;
+                }
+                let answer = parse_answer(&problem, blank, &line, session.input_limit());
+                if answer.is_zero() {
+                    writeln!(self.output, "Type a number")?;
+                } else {
+                    break answer;
+                }
+            };
+            answers.push(answer);
+        }
+        Ok(Some(answers))
+    }
+}
+
+/// Runs sessions in the terminal with the settings and profile `Cli` selects, like the window does.
+pub fn run(cli: &Cli) -> io::Result<()> {
+    let (store, settings) = Store::open(cli);
+    let stdin = io::stdin();
+    let mut trainer = Trainer{input: stdin.lock(), output: io::stdout(), settings, store};
+    trainer.run()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use propinva::engine::Summary;
+    use super::*;
+
+    /// Answers the `count` missing terms `settings` generates, right but for the second one, and returns
+    /// the lines typed with the same session played by the engine alone.
+    fn play(settings: Settings, count: usize) -> (String, Session, Summary) {
+        let mut session = Session::new(settings);
+        session.start();
+        let mut lines = String::new();
+        for index in 0..count {
+            let solution = session.problem().solution()[0].to_u32().unwrap_or(0) + (index == 1) as u32;
+            lines.push_str(&format!("{}\n", solution));
+            session.answer(vec![Rational::from(solution)]);
+            session.next();
+        }
+        let summary = session.finish();
+        (lines, session, summary)
+    }
+
+    #[test]
+    fn a_scripted_session_matches_the_engine() {
+        let settings = Settings{seed: Some(7), length: SessionLength::Count(3), ..Settings::default()};
+        let (lines, session, summary) = play(settings, 3);
+        let mut trainer = Trainer{input: Cursor::new(lines), output: Vec::new(), settings, store: Store::default()};
+        trainer.run().unwrap();
+        let output = String::from_utf8(trainer.output).unwrap();
+
+        assert_eq!(output.matches("] Correct\n").count(), 2);
+        assert_eq!(output.matches("] Inserted ").count(), 1);
+        assert!(output.contains(&summary.headline()));
+        assert!(output.contains(&format!("Correct: {}  Errors: {}  Accuracy: {}%", summary.correct, summary.errors, summary.accuracy)));
+        assert!(output.contains(&format!("Seed: {}", session.seed())));
+        assert!(output.ends_with("r to practise the mistakes, Enter to quit: \n"));
+    }
+}
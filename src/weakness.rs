@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use crate::difficulty::Bounds;
+use crate::engine::Attempt;
+use crate::problem::{Mistake, Problem};
+
+/// A value is only reported as a weakness after this many errors, one slip is not a pattern.
+pub const MIN_ERRORS: u32 = 2;
+
+/// At full adaptive strength a value always answered wrongly is drawn this many times more often than a mastered one.
+pub const MAX_BIAS: f32 = 5.0;
+
+/// How many times each value appeared and how many of those were answered wrongly,
+/// indexed by `value-1`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tally {
+    pub seen: Vec<u32>,
+    pub errors: Vec<u32>,
+}
+impl Tally {
+    pub fn record(&mut self, value: u32, correct: bool) {
+        let index = value as usize - 1;
+        if self.seen.len() <= index {
+            self.seen.resize(index+1, 0);
+            self.errors.resize(index+1, 0);
+        }
+        self.seen[index] += 1;
+        if !correct {
+            self.errors[index] += 1;
+        }
+    }
+    pub fn seen(&self, value: u32) -> u32 {
+        self.seen.get(value as usize - 1).copied().unwrap_or(0)
+    }
+    pub fn errors(&self, value: u32) -> u32 {
+        self.errors.get(value as usize - 1).copied().unwrap_or(0)
+    }
+    /// Share of the appearances of `value` answered wrongly, `None` if it never appeared.
+    pub fn error_rate(&self, value: u32) -> Option<f32> {
+        match self.seen(value) {
+            0 => {None}
+            seen => {Some(self.errors(value) as f32 / seen as f32)}
+        }
+    }
+    pub fn add(&mut self, other: &Tally) {
+        if self.seen.len() < other.seen.len() {
+            self.seen.resize(other.seen.len(), 0);
+            self.errors.resize(other.errors.len(), 0);
+        }
+        for (index, (seen, errors)) in other.seen.iter().zip(&other.errors).enumerate() {
+            self.seen[index] += seen;
+            self.errors[index] += errors;
+        }
+    }
+    /// How likely each value of `bounds` should be drawn: 1 for values never missed, growing with
+    /// the error rate up to `MAX_BIAS` at full `strength` (0 to 1).
+    pub fn weights(&self, bounds: Bounds, strength: f32) -> Vec<f32> {
+        (bounds.min..=bounds.max)
+            .map(|value| {
+                // one extra imaginary right answer, so a single early slip does not dominate
+                let rate = self.errors(value) as f32 / (self.seen(value) + 1) as f32;
+                1.0 + strength.clamp(0.0, 1.0) * (MAX_BIAS - 1.0) * rate
+            })
+            .collect()
+    }
+    /// Values with at least `MIN_ERRORS` errors sharing the highest error rate, in ascending order.
+    pub fn weakest(&self) -> Vec<u32> {
+        let mut weakest = Vec::new();
+        // errors and appearances of the highest rate so far, compared as fractions to keep ties exact
+        let mut best = (0, 1);
+        for value in 1..=self.seen.len() as u32 {
+            let (errors, seen) = (self.errors(value), self.seen(value));
+            if errors < MIN_ERRORS {
+                continue;
+            }
+            match (errors * best.1).cmp(&(best.0 * seen)) {
+                Ordering::Greater => {
+                    weakest = vec![value];
+                    best = (errors, seen);
+                }
+                Ordering::Equal => {weakest.push(value)}
+                Ordering::Less => {}
+            }
+        }
+        weakest
+    }
+}
+
+/// A `Tally` of the base numbers for each multiplier, indexed by `multiplier-1`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Grid {
+    pub rows: Vec<Tally>,
+}
+impl Grid {
+    pub fn record(&mut self, multiplier: u32, number: u32, correct: bool) {
+        let index = multiplier as usize - 1;
+        if self.rows.len() <= index {
+            self.rows.resize(index+1, Tally::default());
+        }
+        self.rows[index].record(number, correct);
+    }
+    pub fn row(&self, multiplier: u32) -> Option<&Tally> {
+        self.rows.get(multiplier as usize - 1)
+    }
+    pub fn add(&mut self, other: &Grid) {
+        if self.rows.len() < other.rows.len() {
+            self.rows.resize(other.rows.len(), Tally::default());
+        }
+        for (row, other) in self.rows.iter_mut().zip(&other.rows) {
+            row.add(other);
+        }
+    }
+}
+
+/// Errors split between the multiplier linking the fractions and the base number being multiplied,
+/// and counted by kind of mistake.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Weakness {
+    pub multipliers: Tally,
+    pub numbers: Tally,
+    /// The errors of each multiplier and base number together.
+    pub pairs: Grid,
+    pub mistakes: BTreeMap<Mistake, u32>,
+    /// The last problem answered with each mistake, to show what the student got wrong.
+    pub examples: BTreeMap<Mistake, Problem>,
+}
+impl Weakness {
+    pub fn from_attempts<'a>(attempts: impl IntoIterator<Item = &'a Attempt>) -> Self {
+        let mut weakness = Weakness::default();
+        for attempt in attempts {
+            weakness.record(attempt);
+        }
+        weakness
+    }
+    pub fn record(&mut self, attempt: &Attempt) {
+        if let Some(multiplier) = attempt.problem.multiplier() {
+            self.multipliers.record(multiplier, attempt.correct);
+        }
+        if let Problem::MissingTerm(exercise) = attempt.problem {
+            let (multiplier, number) = exercise.factors();
+            self.numbers.record(number, attempt.correct);
+            self.pairs.record(multiplier, number, attempt.correct);
+        }
+        if let Err(mistake) = attempt.problem.grade(&attempt.answers) {
+            *self.mistakes.entry(mistake).or_insert(0) += 1;
+            self.examples.insert(mistake, attempt.problem);
+        }
+    }
+    pub fn add(&mut self, other: &Weakness) {
+        self.multipliers.add(&other.multipliers);
+        self.numbers.add(&other.numbers);
+        self.pairs.add(&other.pairs);
+        for (mistake, count) in &other.mistakes {
+            *self.mistakes.entry(*mistake).or_insert(0) += count;
+        }
+        self.examples.extend(other.examples.iter().map(|(mistake, problem)| (*mistake, *problem)));
+    }
+    pub fn weakest_multipliers(&self) -> Vec<u32> {
+        self.multipliers.weakest()
+    }
+    pub fn weakest_numbers(&self) -> Vec<u32> {
+        self.numbers.weakest()
+    }
+    /// The mistakes other than wrong terms with their counts, e.g. `"Not fully reduced: 2, not equivalent: 1"`.
+    pub fn breakdown(&self) -> Option<String> {
+        let counts: Vec<String> = self.mistakes.iter()
+            .filter(|(mistake, _)| **mistake != Mistake::WrongTerm)
+            .map(|(mistake, count)| format!("{}: {}", mistake, count))
+            .collect();
+        if counts.is_empty() {
+            return None;
+        }
+        let mut breakdown = counts.join(", ");
+        breakdown[..1].make_ascii_uppercase();
+        Some(breakdown)
+    }
+    /// One line of advice for the multipliers, one for the base numbers and one per repeated mistake,
+    /// when they show a weakness.
+    pub fn advice(&self) -> Vec<String> {
+        let mut advice = Vec::new();
+        let multipliers = self.weakest_multipliers();
+        match multipliers.len() {
+            0 => {}
+            1 => {advice.push(format!("You should focus on the {} times table", multipliers[0]))}
+            _ => {advice.push(format!("You should focus on the {} times tables", join(&multipliers)))}
+        }
+        let numbers = self.weakest_numbers();
+        if !numbers.is_empty() {
+            advice.push(format!("Take care when multiplying or dividing {}", join(&numbers)));
+        }
+        for (mistake, count) in &self.mistakes {
+            match (mistake.advice(), self.examples.get(mistake)) {
+                _ if *count < MIN_ERRORS => {}
+                (Some(line), Some(Problem::Equivalent(equivalence))) if *mistake != Mistake::Rejected => {
+                    advice.push(format!("Thinks {} = {}: {}", equivalence.a, equivalence.b, line));
+                }
+                (Some(line), _) => {advice.push(String::from(line))}
+                (None, _) => {}
+            }
+        }
+        advice
+    }
+}
+
+/// `[2, 3, 7]` becomes `"2, 3 and 7"`.
+fn join(values: &[u32]) -> String {
+    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+    match values.split_last() {
+        Some((last, [])) => {last.clone()}
+        Some((last, rest)) => {format!("{} and {}", rest.join(", "), last)}
+        None => {String::new()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tally of `(value, seen, errors)`.
+    fn tally(counts: &[(u32, u32, u32)]) -> Tally {
+        let mut tally = Tally::default();
+        for (value, seen, errors) in counts {
+            for index in 0..*seen {
+                tally.record(*value, index >= *errors);
+            }
+        }
+        tally
+    }
+
+    #[test]
+    fn the_weakest_have_the_highest_error_rate_not_the_most_errors() {
+        let tally = tally(&[(3, 10, 3), (7, 2, 2)]);
+        assert_eq!(tally.error_rate(3), Some(0.3));
+        assert_eq!(tally.error_rate(5), None);
+        assert_eq!(tally.weakest(), [7]);
+    }
+
+    #[test]
+    fn ties_are_all_reported_in_ascending_order() {
+        let tally = tally(&[(6, 6, 3), (2, 10, 1), (4, 4, 2)]);
+        assert_eq!(tally.weakest(), [4, 6]);
+        let weakness = Weakness{multipliers: tally, ..Weakness::default()};
+        assert_eq!(weakness.advice(), ["You should focus on the 4 and 6 times tables"]);
+    }
+
+    #[test]
+    fn single_slips_are_not_weaknesses() {
+        let slips = tally(&[(9, 1, 1), (5, 8, MIN_ERRORS - 1)]);
+        assert!(slips.weakest().is_empty());
+        assert!(Weakness{multipliers: slips, ..Weakness::default()}.advice().is_empty());
+        assert_eq!(tally(&[(9, 1, 1), (5, 8, MIN_ERRORS)]).weakest(), [5]);
+    }
+}
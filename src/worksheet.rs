@@ -0,0 +1,354 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::engine::{Session, Settings};
+use crate::layout::{cells, Cell, Term};
+use crate::problem::Problem;
+
+/// A4, in points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+/// Where the first row of problems starts, below the title and the name line.
+const TOP: f32 = 150.0;
+const ROW_HEIGHT: f32 = 66.0;
+const LABEL_WIDTH: f32 = 26.0;
+const TERM_SIZE: f32 = 14.0;
+const BLANK_WIDTH: f32 = 30.0;
+const BLANK_HEIGHT: f32 = 18.0;
+/// Space around each cell of a problem.
+const PADDING: f32 = 4.0;
+
+/// What the pages are drawn with, in points from the top left corner; text stands on its baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Text{x: f32, y: f32, size: f32, text: String},
+    Line{x1: f32, y1: f32, x2: f32, y2: f32},
+    Rect{x: f32, y: f32, width: f32, height: f32},
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Page {
+    pub shapes: Vec<Shape>,
+}
+impl Page {
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        self.shapes.push(Shape::Text{x, y, size, text: String::from(text)});
+    }
+    fn centred_text(&mut self, centre: f32, y: f32, size: f32, text: &str) {
+        // there is no "≠" in the PDF fonts, strike the sign through instead
+        if text == "≠" {
+            let half = text_width("=", size) / 2.0;
+            self.text(centre - half, y, size, "=");
+            self.shapes.push(Shape::Line{x1: centre - half/2.0, y1: y + 1.0, x2: centre + half/2.0, y2: y - size*0.7});
+        } else {
+            self.text(centre - text_width(text, size)/2.0, y, size, text);
+        }
+    }
+}
+
+/// The width of `text` in Helvetica, which both the SVG and the PDF files are set in.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text.chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' => {222}
+            ' ' | '/' | '.' | ',' | ':' | 'f' | 't' | 'I' => {278}
+            'r' | '-' | '(' | ')' => {333}
+            '=' | '<' | '>' | '+' | '×' | '≠' => {584}
+            'A'..='Z' | 'w' => {667}
+            'm' | '%' => {889}
+            _ => {556}
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+fn term_width(term: &Term) -> f32 {
+    match term {
+        Term::Given(text) => {text_width(text, TERM_SIZE)}
+        Term::Blank{solution, ..} => {text_width(solution, TERM_SIZE).max(BLANK_WIDTH)}
+    }
+}
+
+fn cell_width(cell: &Cell) -> f32 {
+    match cell {
+        Cell::Fraction(numerator, denominator) => {term_width(numerator).max(term_width(denominator)) + 2.0*PADDING}
+        Cell::Text(term) => {term_width(term)}
+    }
+}
+
+/// Draws `term` centred on `centre`, its baseline on `y`: the solution in a box on the answer key,
+/// an empty box on the worksheet.
+fn draw_term(page: &mut Page, term: &Term, centre: f32, y: f32, key: bool) {
+    match term {
+        Term::Given(text) => {page.centred_text(centre, y, TERM_SIZE, text)}
+        Term::Blank{solution, ..} => {
+            let width = term_width(term);
+            page.shapes.push(Shape::Rect{x: centre - width/2.0, y: y + 4.0 - BLANK_HEIGHT, width, height: BLANK_HEIGHT});
+            if key {
+                page.centred_text(centre, y, TERM_SIZE, solution);
+            }
+        }
+    }
+}
+
+/// Draws the numbered problem from `x`, the lines of its fractions at `y`.
+fn draw_problem(page: &mut Page, x: f32, y: f32, number: usize, cells: &[Cell], key: bool) {
+    page.text(x, y + 4.0, 11.0, &format!("{}.", number));
+    let mut left = x + LABEL_WIDTH;
+    for cell in cells {
+        let width = cell_width(cell);
+        let centre = left + width/2.0;
+        match cell {
+            Cell::Fraction(numerator, denominator) => {
+                draw_term(page, numerator, centre, y - 8.0, key);
+                page.shapes.push(Shape::Line{x1: left, y1: y, x2: left + width, y2: y});
+                draw_term(page, denominator, centre, y + 18.0, key);
+            }
+            Cell::Text(term) => {draw_term(page, term, centre, y + 5.0, key)}
+        }
+        left += width + 2.0*PADDING;
+    }
+}
+
+fn problem_width(cells: &[Cell]) -> f32 {
+    LABEL_WIDTH + cells.iter().map(|cell| cell_width(cell) + 2.0*PADDING).sum::<f32>()
+}
+
+/// Printable problems made by the session generator, with their answer key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Worksheet {
+    pub settings: Settings,
+    /// The seed the problems were generated from, printed on the pages to make the same sheet again.
+    pub seed: u64,
+    pub problems: Vec<Problem>,
+}
+impl Worksheet {
+    /// The first `count` problems a session of `settings` would serve.
+    pub fn generate(settings: Settings, count: usize) -> Self {
+        let mut session = Session::new(settings);
+        let mut problems = vec![session.start()];
+        while problems.len() < count {
+            problems.push(session.next());
+        }
+        Worksheet{settings, seed: session.seed(), problems}
+    }
+    fn header(&self, key: bool, number: usize) -> Page {
+        let ranges = self.settings.ranges();
+        let mut page = Page::default();
+        page.text(MARGIN, MARGIN + 18.0, 20.0, if key {"Answer key"} else {"Equivalent fractions"});
+        page.text(MARGIN, MARGIN + 38.0, 10.0, &format!("{}, multipliers {}, numbers {}, seed {}, page {}",
+                                                         self.settings.kind, ranges.multiplier, ranges.number, self.seed, number));
+        if !key {
+            page.text(MARGIN, MARGIN + 62.0, 12.0, "Name: ______________________________      Date: ______________");
+        }
+        page.shapes.push(Shape::Line{x1: MARGIN, y1: TOP - 24.0, x2: PAGE_WIDTH - MARGIN, y2: TOP - 24.0});
+        page
+    }
+    /// The problems two to a row, or one when they are too wide, with blank boxes in place of the terms
+    /// and signs to find, or with the solutions in them on the answer key.
+    pub fn pages(&self, key: bool) -> Vec<Page> {
+        let column = (PAGE_WIDTH - 2.0*MARGIN) / 2.0;
+        let mut pages = Vec::new();
+        let mut page = self.header(key, 1);
+        let (mut top, mut left_column) = (TOP, true);
+        for (index, problem) in self.problems.iter().enumerate() {
+            let cells = cells(problem);
+            let wide = problem_width(&cells) > column;
+            if wide && !left_column {
+                top += ROW_HEIGHT;
+                left_column = true;
+            }
+            if top + ROW_HEIGHT > PAGE_HEIGHT - MARGIN {
+                pages.push(std::mem::replace(&mut page, self.header(key, pages.len() + 2)));
+                top = TOP;
+            }
+            let x = if left_column {MARGIN} else {MARGIN + column};
+            draw_problem(&mut page, x, top + ROW_HEIGHT/2.0, index + 1, &cells, key);
+            if wide || !left_column {
+                top += ROW_HEIGHT;
+                left_column = true;
+            } else {
+                left_column = false;
+            }
+        }
+        pages.push(page);
+        pages
+    }
+    /// Writes the worksheet to `path`: a PDF ending with the answer key, or one SVG file per page, the pages
+    /// of the answer key named like `path` with `-answers` added. Returns the files written.
+    pub fn export(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let (sheet, key) = (self.pages(false), self.pages(true));
+        match path.extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_ascii_lowercase()).as_deref() {
+            Some("pdf") => {
+                fs::write(path, pdf(&[sheet, key].concat()))?;
+                Ok(vec![path.to_path_buf()])
+            }
+            Some("svg") => {
+                let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("worksheet");
+                let mut written = Vec::new();
+                for (suffix, pages) in [("", sheet), ("-answers", key)] {
+                    let count = pages.len();
+                    for (index, page) in pages.iter().enumerate() {
+                        let name = if count == 1 {format!("{}{}.svg", stem, suffix)} else {format!("{}{}-{}.svg", stem, suffix, index + 1)};
+                        let file = path.with_file_name(name);
+                        fs::write(&file, svg(page))?;
+                        written.push(file);
+                    }
+                }
+                Ok(written)
+            }
+            _ => {Err(io::Error::new(io::ErrorKind::InvalidInput, format!("cannot tell the format of '{}', expected a .pdf or .svg file", path.display())))}
+        }
+    }
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// `page` as a standalone SVG document.
+pub fn svg(page: &Page) -> String {
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}pt\" height=\"{1}pt\" viewBox=\"0 0 {0} {1}\">\n", PAGE_WIDTH, PAGE_HEIGHT);
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+    svg.push_str("<g font-family=\"Helvetica, Arial, sans-serif\" stroke=\"black\" stroke-width=\"0.8\">\n");
+    for shape in &page.shapes {
+        svg.push_str(&match shape {
+            Shape::Text{x, y, size, text} => {format!("<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{}\" stroke=\"none\">{}</text>\n", x, y, size, escape_xml(text))}
+            Shape::Line{x1, y1, x2, y2} => {format!("<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\n", x1, y1, x2, y2)}
+            Shape::Rect{x, y, width, height} => {format!("<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"none\"/>\n", x, y, width, height)}
+        });
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// `text` as a PDF string in the WinAnsi encoding of the standard fonts.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut string = vec![b'('];
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {string.extend([b'\\', c as u8])}
+            '×' => {string.push(0xD7)}
+            ' '..='~' => {string.push(c as u8)}
+            _ => {string.push(b'?')}
+        }
+    }
+    string.push(b')');
+    string
+}
+
+/// `pages` as a PDF document set in Helvetica, written by hand as it takes nothing more than text and lines.
+pub fn pdf(pages: &[Page]) -> Vec<u8> {
+    // 1: catalog, 2: page tree, 3: font, then each page followed by its content
+    let kids: Vec<String> = (0..pages.len()).map(|index| format!("{} 0 R", 4 + 2*index)).collect();
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    for (index, page) in pages.iter().enumerate() {
+        let mut content = b"0.8 w\n".to_vec();
+        for shape in &page.shapes {
+            match shape {
+                Shape::Text{x, y, size, text} => {
+                    content.extend(format!("BT /F1 {} Tf {:.2} {:.2} Td ", size, x, PAGE_HEIGHT - y).into_bytes());
+                    content.extend(pdf_string(text));
+                    content.extend(b" Tj ET\n");
+                }
+                Shape::Line{x1, y1, x2, y2} => {content.extend(format!("{:.2} {:.2} m {:.2} {:.2} l S\n", x1, PAGE_HEIGHT - y1, x2, PAGE_HEIGHT - y2).into_bytes())}
+                Shape::Rect{x, y, width, height} => {content.extend(format!("{:.2} {:.2} {:.2} {:.2} re S\n", x, PAGE_HEIGHT - y - height, width, height).into_bytes())}
+            }
+        }
+        objects.push(format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                             PAGE_WIDTH, PAGE_HEIGHT, 5 + 2*index).into_bytes());
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    pdf.extend(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).into_bytes());
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::SessionLength;
+    use crate::problem::Kind;
+    use super::*;
+
+    fn worksheet(kind: Kind, count: usize) -> Worksheet {
+        Worksheet::generate(Settings{seed: Some(5), length: SessionLength::Unlimited, kind, ..Settings::default()}, count)
+    }
+
+    /// The numbers of the problems on `page`.
+    fn numbers(page: &Page) -> Vec<usize> {
+        page.shapes.iter()
+            .filter_map(|shape| match shape {
+                Shape::Text{text, ..} => {text.strip_suffix('.').and_then(|number| number.parse().ok())}
+                _ => {None}
+            })
+            .collect()
+    }
+
+    fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+        haystack[from..].windows(needle.len()).position(|window| window == needle).map(|position| from + position)
+    }
+
+    #[test]
+    fn pages_break_after_nine_rows_of_two() {
+        let pages = worksheet(Kind::MissingTerm, 18).pages(false);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(numbers(&pages[0]), (1..=18).collect::<Vec<_>>());
+
+        let pages = worksheet(Kind::MissingTerm, 19).pages(true);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(numbers(&pages[1]), [19]);
+        for page in &pages {
+            assert!(page.shapes.iter().all(|shape| match shape {
+                Shape::Text{y, ..} | Shape::Line{y2: y, ..} => {*y <= PAGE_HEIGHT - MARGIN}
+                Shape::Rect{y, height, ..} => {y + height <= PAGE_HEIGHT - MARGIN}
+            }));
+        }
+    }
+
+    #[test]
+    fn pdf_offsets_and_lengths_point_at_their_objects() {
+        let sheet = worksheet(Kind::Mixed, 30);
+        let pdf = pdf(&[sheet.pages(false), sheet.pages(true)].concat());
+        // the content streams are not UTF-8, the cross-reference table at the end is
+        let startxref = find(&pdf, b"startxref\n", 0).unwrap();
+        let table = std::str::from_utf8(&pdf[startxref..]).unwrap();
+        let xref: usize = table.lines().nth(1).and_then(|offset| offset.parse().ok()).unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n0 "));
+        let entries: Vec<usize> = std::str::from_utf8(&pdf[xref..]).unwrap().lines().skip(3).take_while(|line| line.ends_with(" n ")).map(|line| line[..10].parse().unwrap()).collect();
+        assert!(entries.len() > 3);
+        for (index, offset) in entries.iter().enumerate() {
+            assert!(pdf[*offset..].starts_with(format!("{} 0 obj\n", index + 1).as_bytes()), "object {}", index + 1);
+        }
+
+        let mut from = 0;
+        while let Some(start) = find(&pdf, b"<< /Length ", from) {
+            let header_end = find(&pdf, b" >>\nstream\n", start).unwrap();
+            let length: usize = std::str::from_utf8(&pdf[start + 11..header_end]).unwrap().parse().unwrap();
+            let content = header_end + 11;
+            assert!(pdf[content + length..].starts_with(b"\nendstream"));
+            from = content + length;
+        }
+        assert!(from > 0);
+    }
+}